@@ -15,7 +15,7 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::io;
-use sudoko::{Difficulty, Sudoku, SudokuSolver};
+use sudoko::{Difficulty, Sudoku, SudokuError, SudokuSolver};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum AppMode {
@@ -25,6 +25,8 @@ enum AppMode {
 
 struct SudokuApp {
     puzzle: Sudoku,
+    /// The puzzle as it was loaded or generated, for resetting
+    original: Sudoku,
     cursor: (usize, usize),
     message: String,
     is_solved: bool,
@@ -40,6 +42,7 @@ impl SudokuApp {
         let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
 
         Self {
+            original: puzzle.clone(),
             puzzle,
             cursor: (0, 0),
             message: "Welcome to Sudoku! Use arrow keys to move, 1-9 to fill cells, ? for help"
@@ -72,6 +75,8 @@ impl SudokuApp {
                 KeyCode::Char('s') | KeyCode::Char('S') => self.solve_puzzle(),
                 KeyCode::Char('r') | KeyCode::Char('R') => self.reset_puzzle(),
                 KeyCode::Char('h') | KeyCode::Char('H') => self.get_hint(),
+                KeyCode::Char('n') | KeyCode::Char('N') => self.apply_next_step(),
+                KeyCode::Char('v') | KeyCode::Char('V') => self.check_puzzle(),
                 KeyCode::Char('e') | KeyCode::Char('E') => {
                     self.generate_new_puzzle(Difficulty::Easy)
                 }
@@ -84,6 +89,8 @@ impl SudokuApp {
                 KeyCode::Char('x') | KeyCode::Char('X') => {
                     self.generate_new_puzzle(Difficulty::Expert)
                 }
+                KeyCode::Char('j') | KeyCode::Char('J') => self.load_jigsaw_puzzle(),
+                KeyCode::Char('k') | KeyCode::Char('K') => self.load_killer_puzzle(),
                 KeyCode::Char(c) if c.is_ascii_digit() => {
                     if let Some(digit) = c.to_digit(10) {
                         let value = digit as u8;
@@ -170,16 +177,20 @@ impl SudokuApp {
                 self.is_solved = true;
                 self.message = "Puzzle solved automatically!".to_string();
             }
-            Err(_) => {
+            Err(SudokuError::Unsolvable) => {
                 self.message = "No solution found for this puzzle.".to_string();
             }
+            Err(e @ SudokuError::ContradictoryGivens { .. }) => {
+                self.message = format!("Cannot solve: {}", e);
+            }
+            Err(e) => {
+                self.message = format!("Error: {}", e);
+            }
         }
     }
 
     fn reset_puzzle(&mut self) {
-        let puzzle_str =
-            "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
-        self.puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+        self.puzzle = self.original.clone();
         self.cursor = (0, 0);
         self.is_solved = false;
         self.message = "Puzzle reset to original state.".to_string();
@@ -189,24 +200,73 @@ impl SudokuApp {
         let mut solver = SudokuSolver::new();
         match solver.generate_puzzle(9, difficulty) {
             Ok(new_puzzle) => {
+                // Generation settles for the closest easier grade when it misses the target
+                let achieved = solver
+                    .grade(&new_puzzle)
+                    .map_or(difficulty, |grade| grade.difficulty);
+                self.original = new_puzzle.clone();
                 self.puzzle = new_puzzle;
                 self.cursor = (0, 0);
                 self.is_solved = false;
-                self.message = format!("Generated new {:?} puzzle!", difficulty);
+                self.message = format!(
+                    "Generated new {:?} puzzle with {} clues!",
+                    achieved,
+                    self.puzzle.clue_count()
+                );
+                if achieved != difficulty {
+                    self.message += &format!(" (no {:?} puzzle was found)", difficulty);
+                }
             }
-            Err(_) => {
-                self.message = "Failed to generate new puzzle.".to_string();
+            Err(e) => {
+                self.message = format!("Error: {}", e);
             }
         }
     }
 
+    /// Load a Jigsaw puzzle, whose boxes are irregular regions
+    fn load_jigsaw_puzzle(&mut self) {
+        let puzzle_str =
+            "200000509100000000000240060430100200080000000600009800008070000000600000000000003";
+        let regions =
+            "111222333114222333114255333114255566444256666444555776888999776889999776888899777";
+        self.puzzle = Sudoku::from_jigsaw(puzzle_str, regions, 9).unwrap();
+        self.original = self.puzzle.clone();
+        self.cursor = (0, 0);
+        self.is_solved = false;
+        self.message = "Loaded a Jigsaw puzzle: each region holds 1-9 once".to_string();
+    }
+
+    /// Load a Killer puzzle, whose cages add up to the sums shown
+    fn load_killer_puzzle(&mut self) {
+        let puzzle_str =
+            "010060700003000000000000000000000000000000000000000000000000000000000500007030020";
+        let cages = "23: r1c1 r1c2 r2c2 r2c3 r3c2; 27: r1c3 r1c4 r1c5 r1c6 r2c5; \
+                     15: r1c7 r1c8; 19: r1c9 r2c8 r2c9; 25: r2c1 r3c1 r4c1 r5c1 r6c1; \
+                     26: r2c4 r3c3 r3c4 r3c5 r3c6; 11: r2c6 r2c7 r3c7 r3c8; \
+                     30: r3c9 r4c8 r4c9 r5c7 r5c8; 19: r4c2 r5c2 r6c2 r7c2; \
+                     24: r4c3 r5c3 r6c3 r7c3; 19: r4c4 r4c5 r4c6 r4c7; 9: r5c4 r5c5; \
+                     30: r5c6 r6c5 r6c6 r7c6 r8c6; 11: r5c9 r6c9 r7c9; 17: r6c4 r7c4 r7c5; \
+                     3: r6c7 r6c8; 4: r7c1; 24: r7c7 r8c7 r9c6 r9c7; 12: r7c8 r8c8 r9c8; \
+                     23: r8c1 r8c2 r8c3 r9c1 r9c2; 22: r8c4 r8c5 r9c3 r9c4 r9c5; \
+                     12: r8c9 r9c9";
+        self.puzzle = Sudoku::from_killer(puzzle_str, cages, 9).unwrap();
+        self.original = self.puzzle.clone();
+        self.cursor = (0, 0);
+        self.is_solved = false;
+        self.message = "Loaded a Killer puzzle: each cage adds up to its sum".to_string();
+    }
+
     fn get_hint(&mut self) {
-        let mut solver = SudokuSolver::new();
-        let mut temp_puzzle = self.puzzle.clone();
-        match solver.get_hint(&mut temp_puzzle) {
-            Some((row, col, value)) => {
-                self.cursor = (row, col);
-                self.message = format!("Hint: Try {} at ({}, {})", value, row + 1, col + 1);
+        let solver = SudokuSolver::new();
+        match solver.next_step(&self.puzzle) {
+            Some(step) => {
+                if let Some(&cell) = step.cells.first() {
+                    self.cursor = cell;
+                }
+                self.message = match step.explanation {
+                    Some(ref explanation) => format!("Hint: {} ({})", step, explanation),
+                    None => format!("Hint: {}", step),
+                };
             }
             None => {
                 self.message = "No obvious hints available.".to_string();
@@ -214,6 +274,48 @@ impl SudokuApp {
         }
     }
 
+    /// Check the grid and point at the first problem found
+    fn check_puzzle(&mut self) {
+        let report = self.puzzle.validate_detailed();
+        if let Some(conflict) = report.conflicts.first() {
+            self.cursor = conflict.cells[0];
+            self.message = match report.conflicts.len() {
+                1 => format!("Error: {}", conflict),
+                n => format!("Error: {} (+{} more)", conflict, n - 1),
+            };
+        } else if let Some(error) = report.cage_errors.first() {
+            self.cursor = error.cells[0];
+            self.message = format!("Error: {}", error);
+        } else if let Some(&(row, col)) = report.dead_cells.first() {
+            self.cursor = (row, col);
+            self.message = format!("Error: r{}c{} has no candidates left", row + 1, col + 1);
+        } else if report.is_solved() {
+            self.message = "Puzzle is solved!".to_string();
+        } else {
+            self.message = "No conflicts found".to_string();
+        }
+    }
+
+    /// Replay the solve one logical step at a time
+    fn apply_next_step(&mut self) {
+        let solver = SudokuSolver::new();
+        match solver.solve_step(&mut self.puzzle) {
+            Some(step) => {
+                if let Some(&cell) = step.cells.first() {
+                    self.cursor = cell;
+                }
+                self.message = format!("Step: {}", step);
+
+                if self.puzzle.is_complete() && self.puzzle.is_valid() {
+                    self.is_solved = true;
+                }
+            }
+            None => {
+                self.message = "No logical step found.".to_string();
+            }
+        }
+    }
+
     fn calculate_progress(&self) -> (usize, usize, f64) {
         let total_cells = self.puzzle.size * self.puzzle.size;
         let mut filled_cells = 0;
@@ -245,11 +347,101 @@ impl SudokuApp {
 fn draw_sudoku_grid(f: &mut Frame, app: &SudokuApp, area: Rect) {
     // Create the table data
     let mut rows = Vec::new();
+    let report = app.puzzle.validate_detailed();
+    let conflicting = report.conflicting_cells();
+
+    // Borders run between neighbouring cells in different boxes, so irregular Jigsaw
+    // regions are drawn as well as rectangular boxes
+    let size = app.puzzle.size;
+    let right = |row: usize, col: usize| {
+        col + 1 < size && app.puzzle.box_index(row, col) != app.puzzle.box_index(row, col + 1)
+    };
+    let below = |row: usize, col: usize| {
+        row + 1 < size && app.puzzle.box_index(row, col) != app.puzzle.box_index(row + 1, col)
+    };
+    // Killer cages are outlined with dotted lines between cells in different cages
+    let layout = app.puzzle.layout();
+    let killer = layout.is_killer();
+    let cage_right = |row: usize, col: usize| {
+        killer && col + 1 < size && layout.cage_index(row, col) != layout.cage_index(row, col + 1)
+    };
+    let cage_below = |row: usize, col: usize| {
+        killer && row + 1 < size && layout.cage_index(row, col) != layout.cage_index(row + 1, col)
+    };
+    // Each cage's sum is written in the line above its first cell
+    let cage_sum = |row: usize, col: usize| {
+        let cage = &layout.cages()[layout.cage_index(row, col)?];
+        (cage.cells[0] == (row, col)).then_some(cage.sum)
+    };
+    // Gaps between columns, or rows, with a border anywhere along them get a separator,
+    // and Killer grids separate every cell to make room for the cages
+    let col_gap = |col: usize| col + 1 < size && (killer || (0..size).any(|row| right(row, col)));
+    let row_gap = |row: usize| row + 1 < size && (killer || (0..size).any(|col| below(row, col)));
+    let border_style = Style::default()
+        .fg(Color::Green)
+        .add_modifier(Modifier::BOLD);
+    let cage_style = Style::default().fg(Color::Gray);
+    let sum_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+
+    // The line above `row`, with the borders below the row before it
+    let separator = |row: usize| {
+        let mut separator_cells = Vec::new();
+        let above = row.checked_sub(1);
+        for col in 0..size {
+            let (border, style) = match above {
+                Some(up) if below(up, col) => ("───", border_style),
+                Some(up) if cage_below(up, col) => ("┄┄┄", cage_style),
+                _ => ("   ", border_style),
+            };
+            match cage_sum(row, col) {
+                Some(sum) => {
+                    let sum = sum.to_string();
+                    let rest: String = border.chars().skip(sum.chars().count()).collect();
+                    separator_cells.push(Cell::from(Line::from(vec![
+                        Span::styled(sum, sum_style),
+                        Span::styled(rest, style),
+                    ])));
+                }
+                None => separator_cells.push(Cell::from(border).style(style)),
+            }
+            if col_gap(col) {
+                let (corner, style) = match above {
+                    Some(up) => {
+                        let corner = corner(
+                            right(up, col),
+                            right(row, col),
+                            below(up, col),
+                            below(up, col + 1),
+                        );
+                        let caged = cage_right(up, col)
+                            || cage_right(row, col)
+                            || cage_below(up, col)
+                            || cage_below(up, col + 1);
+                        if corner == " " && caged {
+                            ("·", cage_style)
+                        } else {
+                            (corner, border_style)
+                        }
+                    }
+                    None => (" ", border_style),
+                };
+                separator_cells.push(Cell::from(corner).style(style));
+            }
+        }
+        Row::new(separator_cells).height(1)
+    };
+
+    // Killer grids get a line above the first row for the sums of the cages along it
+    if killer {
+        rows.push(separator(0));
+    }
 
-    for row in 0..app.puzzle.size {
+    for row in 0..size {
         let mut cells = Vec::new();
 
-        for col in 0..app.puzzle.size {
+        for col in 0..size {
             let cell = app.puzzle.get(row, col).unwrap();
             let is_cursor = app.cursor == (row, col);
 
@@ -263,6 +455,12 @@ fn draw_sudoku_grid(f: &mut Frame, app: &SudokuApp, area: Rect) {
                     .bg(Color::Yellow)
                     .fg(Color::Black)
                     .add_modifier(Modifier::BOLD)
+            } else if conflicting.contains(&(row, col)) || report.dead_cells.contains(&(row, col)) {
+                // Broken rules and cells with no candidates left, givens included
+                Style::default()
+                    .bg(Color::Red)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
             } else if cell.is_given() {
                 Style::default()
                     .fg(Color::Cyan)
@@ -283,50 +481,32 @@ fn draw_sudoku_grid(f: &mut Frame, app: &SudokuApp, area: Rect) {
 
             cells.push(Cell::from(value_str).style(cell_style));
 
-            // Add vertical separator for 3x3 blocks
-            if (col + 1) % 3 == 0 && col < app.puzzle.size - 1 {
-                cells.push(
-                    Cell::from("│").style(
-                        Style::default()
-                            .fg(Color::Green)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                );
+            // Separate cells in different boxes
+            if col_gap(col) {
+                let (border, style) = if right(row, col) {
+                    ("│", border_style)
+                } else if cage_right(row, col) {
+                    ("┆", cage_style)
+                } else {
+                    (" ", border_style)
+                };
+                cells.push(Cell::from(border).style(style));
             }
         }
 
         rows.push(Row::new(cells).height(1));
 
-        // Add horizontal separator for 3x3 blocks
-        if (row + 1) % 3 == 0 && row < app.puzzle.size - 1 {
-            let mut separator_cells = Vec::new();
-            for col in 0..app.puzzle.size {
-                separator_cells.push(
-                    Cell::from("───").style(
-                        Style::default()
-                            .fg(Color::Green)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                );
-                if (col + 1) % 3 == 0 && col < app.puzzle.size - 1 {
-                    separator_cells.push(
-                        Cell::from("┼").style(
-                            Style::default()
-                                .fg(Color::Green)
-                                .add_modifier(Modifier::BOLD),
-                        ),
-                    );
-                }
-            }
-            rows.push(Row::new(separator_cells).height(1));
+        // Add a separator line below rows that border another box
+        if row_gap(row) {
+            rows.push(separator(row + 1));
         }
     }
 
     // Create column constraints with separators
     let mut widths = Vec::new();
-    for col in 0..app.puzzle.size {
+    for col in 0..size {
         widths.push(Constraint::Length(3));
-        if (col + 1) % 3 == 0 && col < app.puzzle.size - 1 {
+        if col_gap(col) {
             widths.push(Constraint::Length(1)); // For separator
         }
     }
@@ -344,6 +524,25 @@ fn draw_sudoku_grid(f: &mut Frame, app: &SudokuApp, area: Rect) {
     f.render_widget(table, area);
 }
 
+/// The box-drawing character where borders meet, from the borders running up, down, left
+/// and right of the crossing
+fn corner(up: bool, down: bool, left: bool, right: bool) -> &'static str {
+    match (up, down, left, right) {
+        (true, true, true, true) => "┼",
+        (true, true, true, false) => "┤",
+        (true, true, false, true) => "├",
+        (true, false, true, true) => "┴",
+        (false, true, true, true) => "┬",
+        (false, true, false, true) => "┌",
+        (false, true, true, false) => "┐",
+        (true, false, false, true) => "└",
+        (true, false, true, false) => "┘",
+        (true, _, false, false) | (_, true, false, false) => "│",
+        (false, false, true, _) | (false, false, _, true) => "─",
+        (false, false, false, false) => " ",
+    }
+}
+
 fn draw_status_info(f: &mut Frame, app: &SudokuApp, area: Rect) {
     let status_style = if app.is_solved {
         Style::default()
@@ -437,7 +636,11 @@ fn draw_controls(f: &mut Frame, area: Rect) {
             Span::styled("R", Style::default().fg(Color::Blue)),
             Span::raw(" Reset  "),
             Span::styled("H", Style::default().fg(Color::Magenta)),
-            Span::raw(" Hint"),
+            Span::raw(" Hint  "),
+            Span::styled("N", Style::default().fg(Color::Magenta)),
+            Span::raw(" Next step  "),
+            Span::styled("V", Style::default().fg(Color::Magenta)),
+            Span::raw(" Check"),
         ]),
         Line::from(vec![
             Span::styled("E", Style::default().fg(Color::Green)),
@@ -445,7 +648,11 @@ fn draw_controls(f: &mut Frame, area: Rect) {
             Span::styled("M", Style::default().fg(Color::Yellow)),
             Span::raw(" Medium  "),
             Span::styled("D", Style::default().fg(Color::Red)),
-            Span::raw(" Hard"),
+            Span::raw(" Hard  "),
+            Span::styled("J", Style::default().fg(Color::Cyan)),
+            Span::raw(" Jigsaw  "),
+            Span::styled("K", Style::default().fg(Color::Cyan)),
+            Span::raw(" Killer"),
         ]),
         Line::from(vec![
             Span::styled("?", Style::default().fg(Color::Cyan)),
@@ -485,12 +692,16 @@ fn draw_help(f: &mut Frame, area: Rect) {
         Line::from("• S: Automatically solve the entire puzzle"),
         Line::from("• R: Reset puzzle to original state"),
         Line::from("• H: Get a hint for the current puzzle"),
+        Line::from("• N: Apply the next logical step and show why"),
+        Line::from("• V: Check the grid and jump to the first conflict"),
         Line::from(""),
         Line::from("PUZZLE GENERATION:"),
         Line::from("• E: Generate new Easy puzzle"),
         Line::from("• M: Generate new Medium puzzle"),
         Line::from("• D: Generate new Hard puzzle"),
         Line::from("• X: Generate new Expert puzzle"),
+        Line::from("• J: Load a Jigsaw puzzle, whose boxes are irregular regions"),
+        Line::from("• K: Load a Killer puzzle, whose dotted cages add up to their sums"),
         Line::from(""),
         Line::from("VISUAL CUES:"),
         Line::from(vec![
@@ -524,6 +735,14 @@ fn draw_help(f: &mut Frame, area: Rect) {
             ),
             Span::raw(": Invalid numbers (violate rules or don't match solution)"),
         ]),
+        Line::from(vec![
+            Span::raw("• "),
+            Span::styled(
+                "Red background",
+                Style::default().bg(Color::Red).fg(Color::White),
+            ),
+            Span::raw(": Cells in a conflict, or empty cells with no candidates left"),
+        ]),
         Line::from(""),
         Line::from("PROGRESS TRACKING:"),
         Line::from("• Visual progress bar shows completion percentage"),
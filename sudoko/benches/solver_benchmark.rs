@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use sudoko::{Sudoku, SudokuSolver};
+use sudoko::{SolverBackend, Sudoku, SudokuSolver};
 
 fn benchmark_9x9_solve(c: &mut Criterion) {
     let puzzle_str =
@@ -15,7 +15,7 @@ fn benchmark_9x9_solve(c: &mut Criterion) {
 }
 
 fn benchmark_4x4_solve(c: &mut Criterion) {
-    let puzzle_str = "1.3..2.43.1..4.2";
+    let puzzle_str = "1.3..41.2..3..2.";
     let puzzle = Sudoku::from_string(puzzle_str, 4).unwrap();
 
     c.bench_function("solve 4x4", |b| {
@@ -46,6 +46,56 @@ fn benchmark_candidates(c: &mut Criterion) {
     });
 }
 
+fn benchmark_placement_check(c: &mut Criterion) {
+    let puzzle_str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    c.bench_function("check placement", |b| {
+        b.iter(|| black_box(&puzzle).is_valid_placement(0, 2, 4))
+    });
+}
+
+/// Solve with no logical strategies using the given backend
+fn search_solver(backend: SolverBackend) -> SudokuSolver {
+    let mut solver = SudokuSolver::new_with_strategies(vec![]);
+    solver.set_backend(backend);
+    solver
+}
+
+fn benchmark_search_backends(c: &mut Criterion) {
+    let hard_9x9 = Sudoku::from_string(
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+        9,
+    )
+    .unwrap();
+    let puzzle_16x16 = Sudoku::from_string("A..49.7.....BCG5.DC.E64..9..F73.....25.3.8.4.......5AD.G..C...2.93.G7...BA..EF..4B1......5F3A.......8.3F.......2.2..BA..........C...1......G....3.......F7.E8......84.6......51D..AD..8C...52.E.......G.2.5A........6.2.9.B8.4...F4A..E..G...28.2CB7.8...4..1..G", 16).unwrap();
+    let puzzle_25x25 = Sudoku::from_string("...J.D......N....53.A.4.6D9B35..IM.1....2.FP.J..C..2...A..J.....M...I.3O.N.A.E....5..........CD.KM.7.O1N.......L5D....8A.EBP..FA..J.P15.........N84..B...O.FHN.........P..C.J.15421...K.C....N.EM7H.D..FI...BM9..A2.F....O.........P6K.72.D..HIA..B1.53NMO.G.AF..E.IJ9....73..6...ME.4I.......1...D.....5...H.......4.....I.........A.5J9.....O...........B1.L1.L.O..95....K.H.8..EF.I..NMC.L1J..BK...5.GD..6.........GA....6.C...94....N6..L.9I.B.....57JC...AK124.5.D..C......8N.6AB.JO..9..P.5.D...2...8FH.I..LB..HFE.IB.K.8693.P.....2C5.NA9G.P.....H.....IB.....D.6.4L.M.DFP....K8..3...9..3D.2.NH.JM...K.C7..I16E.CPK...5.9...AN.....1B7.O.", 25).unwrap();
+
+    for (name, backend) in [
+        ("backtracking", SolverBackend::Backtracking),
+        ("dlx", SolverBackend::DancingLinks),
+    ] {
+        c.bench_function(&format!("{} solve 9x9 hard", name), |b| {
+            b.iter(|| {
+                search_solver(backend)
+                    .solve(black_box(hard_9x9.clone()))
+                    .unwrap()
+            })
+        });
+        c.bench_function(&format!("{} count solutions 16x16", name), |b| {
+            b.iter(|| search_solver(backend).count_solutions(black_box(puzzle_16x16.clone()), 2))
+        });
+        c.bench_function(&format!("{} solve 25x25", name), |b| {
+            b.iter(|| {
+                search_solver(backend)
+                    .solve(black_box(puzzle_25x25.clone()))
+                    .unwrap()
+            })
+        });
+    }
+}
+
 fn benchmark_puzzle_generation(c: &mut Criterion) {
     c.bench_function("generate 9x9 medium", |b| {
         b.iter(|| {
@@ -60,9 +110,11 @@ fn benchmark_puzzle_generation(c: &mut Criterion) {
 criterion_group!(
     benches,
     benchmark_9x9_solve,
-    // benchmark_4x4_solve,
+    benchmark_4x4_solve,
     benchmark_validation,
     benchmark_candidates,
+    benchmark_placement_check,
+    benchmark_search_backends,
     benchmark_puzzle_generation
 );
 criterion_main!(benches);
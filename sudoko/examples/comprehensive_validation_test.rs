@@ -6,7 +6,7 @@ fn main() {
     // Test case 1: Original puzzle with various invalid placements
     let puzzle_str =
         "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
-    let mut puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
 
     // Get correct solution
     let mut solver = SudokuSolver::new();
@@ -1,4 +1,4 @@
-use sudoko::{Difficulty, Sudoku, SudokuSolver};
+use sudoko::{Sudoku, SudokuSolver};
 
 fn main() {
     println!("Testing enhanced validation (rules + solution correctness)...");
@@ -1,4 +1,4 @@
-use sudoko::{Difficulty, Sudoku, SudokuSolver};
+use sudoko::Sudoku;
 
 fn main() {
     println!("Testing validation methods...");
@@ -45,7 +45,7 @@ fn main() {
     println!("\nTesting with a real puzzle:");
     let puzzle_str =
         "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
-    let mut puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
 
     // Test invalid placement
     println!("Testing placement of 5 at (0,0) in real puzzle:");
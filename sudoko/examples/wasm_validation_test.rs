@@ -1,3 +1,4 @@
+#[cfg(feature = "wasm")]
 use sudoko::WasmSudoku;
 
 #[cfg(feature = "wasm")]
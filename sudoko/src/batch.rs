@@ -0,0 +1,299 @@
+//! Solving collections of puzzles stored one per line, and generating many at once
+
+use crate::error::SudokuError;
+use crate::generator::GenerationOptions;
+use crate::layout::Layout;
+use crate::solver::{SolverStats, SudokuSolver};
+use crate::sudoku::Sudoku;
+use crate::variant::Variant;
+use std::collections::VecDeque;
+use std::io::{BufRead, Lines};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The outcome of solving one puzzle of a collection
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// Line of the input the puzzle was on, counting from 1
+    pub line: usize,
+    /// The puzzle as it was written
+    pub puzzle: String,
+    pub solution: Result<Sudoku, SudokuError>,
+    /// Statistics of the solve; empty if it failed
+    pub stats: SolverStats,
+    pub elapsed: Duration,
+}
+
+impl BatchResult {
+    pub fn is_solved(&self) -> bool {
+        self.solution.is_ok()
+    }
+}
+
+/// Reads puzzles one per line and solves each in turn, or several at once on separate
+/// threads, always giving the results in input order.
+///
+/// The puzzle is the first word on a line, so anything after it, such as a rating, is
+/// ignored, as are blank lines and lines starting with `#`. Without a fixed size, each
+/// puzzle's size is worked out from its length.
+pub struct BatchSolver<R> {
+    lines: Lines<R>,
+    line: usize,
+    size: Option<usize>,
+    /// Rows and columns of each box, when not the usual shape for the size
+    box_shape: Option<(usize, usize)>,
+    variants: Vec<Variant>,
+    solver: SudokuSolver,
+    jobs: usize,
+    /// Solved puzzles not yet handed out
+    ready: VecDeque<BatchResult>,
+    done: bool,
+}
+
+impl<R: BufRead> BatchSolver<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+            size: None,
+            box_shape: None,
+            variants: Vec::new(),
+            solver: SudokuSolver::new(),
+            jobs: 1,
+            ready: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Read every puzzle as `size` x `size`
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self.box_shape = None;
+        self
+    }
+
+    /// Read every puzzle on a grid of boxes `box_rows` tall and `box_cols` wide
+    pub fn with_box_shape(mut self, box_rows: usize, box_cols: usize) -> Self {
+        self.size = Some(box_rows * box_cols);
+        self.box_shape = Some((box_rows, box_cols));
+        self
+    }
+
+    /// Play every puzzle with a variant rule as well
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        if !self.variants.contains(&variant) {
+            self.variants.push(variant);
+        }
+        self
+    }
+
+    /// Solve with `solver` and its settings instead of a default one
+    pub fn with_solver(mut self, solver: SudokuSolver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    /// Solve up to `jobs` puzzles at a time, each thread with its own clone of the solver
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Read the next puzzles, as many as keep the threads busy, and solve them
+    fn solve_next_puzzles(&mut self) {
+        let wanted = if self.jobs == 1 {
+            1
+        } else {
+            self.jobs * PUZZLES_PER_JOB
+        };
+        let mut puzzles = Vec::new();
+        let mut read_error = None;
+
+        while puzzles.len() < wanted {
+            self.line += 1;
+            let text = match self.lines.next() {
+                Some(Ok(text)) => text,
+                Some(Err(e)) => {
+                    // Report the read error once, then stop
+                    read_error = Some(BatchResult {
+                        line: self.line,
+                        puzzle: String::new(),
+                        solution: Err(e.into()),
+                        stats: SolverStats::new(),
+                        elapsed: Duration::ZERO,
+                    });
+                    self.done = true;
+                    break;
+                }
+                None => {
+                    self.done = true;
+                    break;
+                }
+            };
+            match text.split_whitespace().next() {
+                Some(puzzle) if !puzzle.starts_with('#') => {
+                    puzzles.push((self.line, puzzle.to_string()))
+                }
+                _ => continue,
+            }
+        }
+
+        let size = self.size;
+        let box_shape = self.box_shape;
+        let variants = &self.variants;
+        let results = parallel_map(
+            &puzzles,
+            self.jobs,
+            &self.solver,
+            |solver, (line, puzzle)| solve_line(solver, size, box_shape, variants, *line, puzzle),
+        );
+        self.ready.extend(results);
+        self.ready.extend(read_error);
+    }
+}
+
+impl<R: BufRead> Iterator for BatchSolver<R> {
+    type Item = BatchResult;
+
+    fn next(&mut self) -> Option<BatchResult> {
+        while self.ready.is_empty() && !self.done {
+            self.solve_next_puzzles();
+        }
+        self.ready.pop_front()
+    }
+}
+
+/// A generated puzzle and the seed that makes it again
+#[derive(Debug, Clone)]
+pub struct GeneratedPuzzle {
+    pub seed: u64,
+    pub puzzle: Result<Sudoku, SudokuError>,
+}
+
+/// Generate `count` puzzles meeting `options` on up to `jobs` threads.
+///
+/// Puzzle `i` is generated from the seed in `options` plus `i`, or a random seed plus `i`
+/// without one, so the same seed gives the same puzzles, in the same order, whatever the
+/// number of jobs.
+pub fn generate_batch(
+    size: usize,
+    options: &GenerationOptions,
+    count: usize,
+    jobs: usize,
+) -> Result<Vec<GeneratedPuzzle>, SudokuError> {
+    if Layout::box_shape_for(size).is_none() {
+        return Err(SudokuError::InvalidSize(size));
+    }
+
+    let first_seed = options.seed.unwrap_or_else(rand::random);
+    let seeds: Vec<u64> = (0..count as u64)
+        .map(|i| first_seed.wrapping_add(i))
+        .collect();
+
+    Ok(parallel_map(
+        &seeds,
+        jobs,
+        &SudokuSolver::new(),
+        |solver, &seed| {
+            let options = options.clone().with_seed(seed);
+            GeneratedPuzzle {
+                seed,
+                puzzle: solver.generate_with_options(size, &options),
+            }
+        },
+    ))
+}
+
+/// Puzzles read ahead for each thread, so none sits idle waiting for a slow one
+const PUZZLES_PER_JOB: usize = 16;
+
+/// Parse and solve the puzzle on one line, timing it
+fn solve_line(
+    solver: &mut SudokuSolver,
+    size: Option<usize>,
+    box_shape: Option<(usize, usize)>,
+    variants: &[Variant],
+    line: usize,
+    puzzle: &str,
+) -> BatchResult {
+    let start = Instant::now();
+    let size = size.unwrap_or_else(|| size_for_length(puzzle.chars().count()));
+    let layout = match box_shape {
+        Some((box_rows, box_cols)) => Layout::rectangular(box_rows, box_cols),
+        None => Layout::standard(size),
+    };
+    let (solution, stats) = match layout
+        .and_then(|mut layout| {
+            for &variant in variants {
+                layout.add_variant(variant)?;
+            }
+            Sudoku::from_string_with_layout(puzzle, layout)
+        })
+        .and_then(|sudoku| solver.solve_with_stats(sudoku))
+    {
+        Ok((solution, stats)) => (Ok(solution), stats),
+        Err(e) => (Err(e), SolverStats::new()),
+    };
+    BatchResult {
+        line,
+        puzzle: puzzle.to_string(),
+        solution,
+        stats,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Apply `f` to every item on up to `jobs` threads, each with its own clone of `solver`,
+/// and collect the results in the order of the items
+fn parallel_map<T, U, F>(items: &[T], jobs: usize, solver: &SudokuSolver, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&mut SudokuSolver, &T) -> U + Sync,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    if jobs == 1 {
+        let mut solver = solver.clone();
+        return items.iter().map(|item| f(&mut solver, item)).collect();
+    }
+
+    // Threads take the next unclaimed item until none are left
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<U>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut solver = solver.clone();
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= items.len() {
+                            return done;
+                        }
+                        done.push((i, f(&mut solver, &items[i])));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            let done = worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (i, result) in done {
+                results[i] = Some(result);
+            }
+        }
+    });
+    results.into_iter().flatten().collect()
+}
+
+/// The grid size with boxes, 4x4 or larger, whose puzzles are closest to `length`
+/// characters long
+fn size_for_length(length: usize) -> usize {
+    (4..u32::BITS as usize)
+        .filter(|&size| Layout::box_shape_for(size).is_some())
+        .min_by_key(|&size| (size * size).abs_diff(length))
+        .unwrap_or(4)
+}
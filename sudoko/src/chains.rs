@@ -0,0 +1,813 @@
+//! Chain strategies: Simple Coloring, X-Chains and Alternating Inference Chains
+
+use crate::strategies::{dedup_eliminations, push_step, Elimination, SolvingStrategy, Step};
+use crate::sudoku::Sudoku;
+use crate::wings::eliminate_from_common_peers;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Pairs of cells that are the only two places for `value` in some unit (strong links)
+pub(crate) fn conjugate_pairs(sudoku: &Sudoku, value: u8) -> Vec<((usize, usize), (usize, usize))> {
+    let mut pairs = Vec::new();
+
+    for unit in sudoku.units() {
+        let cells: Vec<(usize, usize)> = sudoku
+            .unit_cells(unit)
+            .into_iter()
+            .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+            .collect();
+        if cells.len() == 2 && !pairs.contains(&(cells[0], cells[1])) {
+            pairs.push((cells[0], cells[1]));
+        }
+    }
+
+    pairs
+}
+
+/// Cells that still have `value` as a candidate
+fn candidate_cells(sudoku: &Sudoku, value: u8) -> Vec<(usize, usize)> {
+    (0..sudoku.size)
+        .flat_map(|row| (0..sudoku.size).map(move |col| (row, col)))
+        .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+        .collect()
+}
+
+/// Simple Coloring: Color the cells of each network of strong links for a value with
+/// alternating colors; exactly one color holds the value
+pub struct SimpleColoring;
+
+impl SimpleColoring {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+
+        for value in 1..=sudoku.size as u8 {
+            let pairs = conjugate_pairs(sudoku, value);
+            let cells = candidate_cells(sudoku, value);
+            let mut colors: Vec<Option<(usize, bool)>> = vec![None; cells.len()];
+            let index = |cell: (usize, usize)| cells.iter().position(|&c| c == cell).unwrap();
+
+            let mut component = 0;
+            for start in 0..cells.len() {
+                if colors[start].is_some()
+                    || !pairs
+                        .iter()
+                        .any(|&(a, b)| a == cells[start] || b == cells[start])
+                {
+                    continue;
+                }
+
+                // Breadth-first walk over the strong links, alternating colors
+                colors[start] = Some((component, true));
+                let mut members = vec![start];
+                let mut queue = VecDeque::from([start]);
+                while let Some(current) = queue.pop_front() {
+                    let (_, color) = colors[current].unwrap();
+                    for &(a, b) in &pairs {
+                        let other = if a == cells[current] {
+                            b
+                        } else if b == cells[current] {
+                            a
+                        } else {
+                            continue;
+                        };
+                        let other = index(other);
+                        if colors[other].is_none() {
+                            colors[other] = Some((component, !color));
+                            members.push(other);
+                            queue.push_back(other);
+                        }
+                    }
+                }
+
+                let colored = |color: bool| -> Vec<(usize, usize)> {
+                    members
+                        .iter()
+                        .filter(|&&i| colors[i] == Some((component, color)))
+                        .map(|&i| cells[i])
+                        .collect()
+                };
+                let (on, off) = (colored(true), colored(false));
+                let mut eliminations = Vec::new();
+
+                // Color wrap: two cells of one color see each other, so that color is false
+                for group in [&on, &off] {
+                    let contradicts = group
+                        .iter()
+                        .enumerate()
+                        .any(|(i, &a)| group[i + 1..].iter().any(|&b| sudoku.sees(a, b)));
+                    if contradicts {
+                        for &(row, col) in group {
+                            eliminations.push(Elimination::new(row, col, value));
+                        }
+                    }
+                }
+
+                // Color trap: an uncolored cell seeing both colors cannot hold the value
+                for &cell in &cells {
+                    if on.contains(&cell) || off.contains(&cell) {
+                        continue;
+                    }
+                    if on.iter().any(|&c| sudoku.sees(cell, c))
+                        && off.iter().any(|&c| sudoku.sees(cell, c))
+                    {
+                        eliminations.push(Elimination::new(cell.0, cell.1, value));
+                    }
+                }
+
+                let step = Step::elimination(self.name(), eliminations)
+                    .with_cells(on.into_iter().chain(off).collect());
+                push_step(&mut steps, step);
+                component += 1;
+            }
+        }
+
+        steps
+    }
+}
+
+impl SolvingStrategy for SimpleColoring {
+    fn name(&self) -> &'static str {
+        "Simple Coloring"
+    }
+
+    fn rating(&self) -> f32 {
+        6.5
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
+
+/// X-Chains: An alternating chain of strong and weak links on one value that starts and
+/// ends with a strong link; one end must hold the value, so cells seeing both ends cannot
+pub struct XChains;
+
+impl XChains {
+    /// Shortest alternating chains from `start`, returned as the cell paths of every chain
+    /// of at least three links
+    fn chains_from(
+        &self,
+        cells: &[(usize, usize)],
+        strong: &[Vec<usize>],
+        weak: &[Vec<usize>],
+        start: usize,
+    ) -> Vec<Vec<usize>> {
+        // State: (cell, whether the next link must be strong)
+        let state = |cell: usize, strong_next: bool| cell * 2 + strong_next as usize;
+        let mut parent: Vec<Option<usize>> = vec![None; cells.len() * 2];
+        let mut seen = vec![false; cells.len() * 2];
+        let mut chains = Vec::new();
+
+        seen[state(start, true)] = true;
+        let mut queue = VecDeque::from([(start, true)]);
+        while let Some((current, strong_next)) = queue.pop_front() {
+            let next = if strong_next {
+                &strong[current]
+            } else {
+                &weak[current]
+            };
+
+            for &other in next {
+                let next_state = state(other, !strong_next);
+                if seen[next_state] {
+                    continue;
+                }
+                seen[next_state] = true;
+                parent[next_state] = Some(state(current, strong_next));
+                queue.push_back((other, !strong_next));
+
+                if strong_next {
+                    let mut path = vec![other];
+                    let mut at = parent[next_state];
+                    while let Some(s) = at {
+                        path.push(s / 2);
+                        at = parent[s];
+                    }
+                    path.reverse();
+
+                    let mut unique = path.clone();
+                    unique.sort_unstable();
+                    unique.dedup();
+                    if path.len() >= 4 && unique.len() == path.len() {
+                        chains.push(path);
+                    }
+                }
+            }
+        }
+
+        chains
+    }
+
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+
+        for value in 1..=sudoku.size as u8 {
+            let cells = candidate_cells(sudoku, value);
+            let mut strong = vec![Vec::new(); cells.len()];
+            for (a, b) in conjugate_pairs(sudoku, value) {
+                let ia = cells.iter().position(|&c| c == a).unwrap();
+                let ib = cells.iter().position(|&c| c == b).unwrap();
+                strong[ia].push(ib);
+                strong[ib].push(ia);
+            }
+
+            // A weak link is only useful if a strong link can follow it
+            let weak: Vec<Vec<usize>> = cells
+                .iter()
+                .map(|&cell| {
+                    (0..cells.len())
+                        .filter(|&other| !strong[other].is_empty())
+                        .filter(|&other| sudoku.sees(cell, cells[other]))
+                        .collect()
+                })
+                .collect();
+
+            for start in (0..cells.len()).filter(|&i| !strong[i].is_empty()) {
+                for chain in self.chains_from(&cells, &strong, &weak, start) {
+                    let first = cells[chain[0]];
+                    let last = cells[*chain.last().unwrap()];
+                    let eliminations = cells
+                        .iter()
+                        .filter(|&&cell| sudoku.sees(cell, first) && sudoku.sees(cell, last))
+                        .map(|&(row, col)| Elimination::new(row, col, value))
+                        .collect();
+                    let step = Step::elimination(self.name(), eliminations)
+                        .with_cells(chain.iter().map(|&i| cells[i]).collect());
+                    push_step(&mut steps, step);
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+impl SolvingStrategy for XChains {
+    fn name(&self) -> &'static str {
+        "X-Chains"
+    }
+
+    fn rating(&self) -> f32 {
+        6.6
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
+
+/// A chain node: `value` in a single cell, or somewhere in a group of cells sharing two units
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainNode {
+    pub cells: Vec<(usize, usize)>,
+    pub value: u8,
+}
+
+impl ChainNode {
+    pub fn is_group(&self) -> bool {
+        self.cells.len() > 1
+    }
+
+    /// Describe the node being true (`holds`) or false, e.g. "r3c5 is 7" or "none of r3c4,5 is 7"
+    fn describe(&self, holds: bool) -> String {
+        match (self.is_group(), holds) {
+            (false, true) => format!("{} is {}", self.location(), self.value),
+            (false, false) => format!("{} is not {}", self.location(), self.value),
+            (true, true) => format!("one of {} is {}", self.location(), self.value),
+            (true, false) => format!("none of {} is {}", self.location(), self.value),
+        }
+    }
+
+    /// Cells in row/column notation; groups share a row or a column, e.g. "r3c4,5"
+    fn location(&self) -> String {
+        let (row, col) = self.cells[0];
+        let join = |values: Vec<usize>| {
+            values
+                .iter()
+                .map(|v| (v + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        if self.cells.iter().all(|&(r, _)| r == row) {
+            format!(
+                "r{}c{}",
+                row + 1,
+                join(self.cells.iter().map(|&(_, c)| c).collect())
+            )
+        } else if self.cells.iter().all(|&(_, c)| c == col) {
+            format!(
+                "r{}c{}",
+                join(self.cells.iter().map(|&(r, _)| r).collect()),
+                col + 1
+            )
+        } else {
+            self.cells
+                .iter()
+                .map(|&(r, c)| format!("r{}c{}", r + 1, c + 1))
+                .collect::<Vec<_>>()
+                .join("|")
+        }
+    }
+}
+
+impl fmt::Display for ChainNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}){}", self.value, self.location())
+    }
+}
+
+/// Strong link: at least one end is true. Weak link: at most one end is true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    Strong,
+    Weak,
+}
+
+/// An alternating chain of nodes; `links[i]` joins `nodes[i]` and `nodes[i + 1]`.
+/// For loops the last node repeats the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub nodes: Vec<ChainNode>,
+    pub links: Vec<LinkKind>,
+    pub is_loop: bool,
+}
+
+impl Chain {
+    /// Plain-language walk through the chain, starting from the first node being false
+    /// (or true, for chains that start with a weak link)
+    pub fn explain(&self) -> String {
+        let mut holds = self.links.first() == Some(&LinkKind::Weak);
+        let mut text = format!("If {}", self.nodes[0].describe(holds));
+
+        for (link, node) in self.links.iter().zip(&self.nodes[1..]) {
+            holds = *link == LinkKind::Strong;
+            text.push_str(&format!(", then {}", node.describe(holds)));
+        }
+
+        let first = &self.nodes[0];
+        let last = &self.nodes[self.nodes.len() - 1];
+        if self.is_loop {
+            text.push_str(". The loop is continuous, so every weak link in it is also strong");
+        } else if first == last {
+            text.push_str(&format!(
+                ". The assumption contradicts itself, so {}",
+                first.describe(holds)
+            ));
+        } else {
+            text.push_str(&format!(
+                ". Either {} or {}",
+                first.describe(true),
+                last.describe(true)
+            ));
+        }
+        text
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nodes[0])?;
+        for (link, node) in self.links.iter().zip(&self.nodes[1..]) {
+            let symbol = match link {
+                LinkKind::Strong => "=",
+                LinkKind::Weak => "-",
+            };
+            write!(f, "{}{}", symbol, node)?;
+        }
+        Ok(())
+    }
+}
+
+/// A chain together with the candidates it removes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainDeduction {
+    pub chain: Chain,
+    pub eliminations: Vec<Elimination>,
+}
+
+/// Nodes and links between every candidate (and grouped candidate) of the grid
+struct InferenceGraph {
+    nodes: Vec<ChainNode>,
+    index: HashMap<ChainNode, usize>,
+    strong: Vec<Vec<usize>>,
+    /// Every link usable as weak, which includes the strong ones
+    weak: Vec<Vec<usize>>,
+}
+
+impl InferenceGraph {
+    fn build(sudoku: &Sudoku) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            strong: Vec::new(),
+            weak: Vec::new(),
+        };
+
+        for row in 0..sudoku.size {
+            for col in 0..sudoku.size {
+                for value in sudoku.candidate_values(row, col) {
+                    graph.add_node(ChainNode {
+                        cells: vec![(row, col)],
+                        value,
+                    });
+                }
+            }
+        }
+
+        // Groups: the candidates for a value where two units intersect
+        let unit_cells: Vec<Vec<(usize, usize)>> = sudoku
+            .units()
+            .into_iter()
+            .map(|unit| sudoku.unit_cells(unit))
+            .collect();
+        for (i, a) in unit_cells.iter().enumerate() {
+            for b in &unit_cells[i + 1..] {
+                let shared: Vec<(usize, usize)> =
+                    a.iter().copied().filter(|cell| b.contains(cell)).collect();
+                if shared.len() < 2 {
+                    continue;
+                }
+                for value in 1..=sudoku.size as u8 {
+                    let cells: Vec<(usize, usize)> = shared
+                        .iter()
+                        .copied()
+                        .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+                        .collect();
+                    if cells.len() >= 2 {
+                        graph.add_node(ChainNode { cells, value });
+                    }
+                }
+            }
+        }
+
+        graph.strong = vec![Vec::new(); graph.nodes.len()];
+        graph.weak = vec![Vec::new(); graph.nodes.len()];
+        graph.link_units(sudoku, &unit_cells);
+        graph.link_cells(sudoku);
+        graph.link_peers(sudoku);
+        graph
+    }
+
+    fn add_node(&mut self, node: ChainNode) {
+        if !self.index.contains_key(&node) {
+            self.index.insert(node.clone(), self.nodes.len());
+            self.nodes.push(node);
+        }
+    }
+
+    fn add_link(&mut self, a: usize, b: usize, kind: LinkKind) {
+        if kind == LinkKind::Strong && !self.strong[a].contains(&b) {
+            self.strong[a].push(b);
+            self.strong[b].push(a);
+        }
+        if !self.weak[a].contains(&b) {
+            self.weak[a].push(b);
+            self.weak[b].push(a);
+        }
+    }
+
+    /// Strong links between two nodes that hold every candidate for a value in a unit
+    fn link_units(&mut self, sudoku: &Sudoku, unit_cells: &[Vec<(usize, usize)>]) {
+        let by_value = self.nodes_by_value(sudoku);
+        for cells in unit_cells {
+            for value in 1..=sudoku.size as u8 {
+                let candidates: Vec<(usize, usize)> = cells
+                    .iter()
+                    .copied()
+                    .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+                    .collect();
+                if candidates.len() < 2 {
+                    continue;
+                }
+
+                for &a in &by_value[value as usize - 1] {
+                    let node = &self.nodes[a];
+                    if !node.cells.iter().all(|c| candidates.contains(c)) {
+                        continue;
+                    }
+                    let rest = ChainNode {
+                        cells: candidates
+                            .iter()
+                            .copied()
+                            .filter(|c| !node.cells.contains(c))
+                            .collect(),
+                        value,
+                    };
+                    if let Some(&b) = self.index.get(&rest) {
+                        self.add_link(a, b, LinkKind::Strong);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Links between the candidates of one cell; strong when the cell is bivalue
+    fn link_cells(&mut self, sudoku: &Sudoku) {
+        for row in 0..sudoku.size {
+            for col in 0..sudoku.size {
+                let ids: Vec<usize> = sudoku
+                    .candidate_values(row, col)
+                    .into_iter()
+                    .map(|value| {
+                        self.index[&ChainNode {
+                            cells: vec![(row, col)],
+                            value,
+                        }]
+                    })
+                    .collect();
+                let kind = if ids.len() == 2 {
+                    LinkKind::Strong
+                } else {
+                    LinkKind::Weak
+                };
+                for (i, &a) in ids.iter().enumerate() {
+                    for &b in &ids[i + 1..] {
+                        self.add_link(a, b, kind);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Weak links between nodes of the same value whose cells all see each other
+    fn link_peers(&mut self, sudoku: &Sudoku) {
+        for ids in self.nodes_by_value(sudoku) {
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    let (x, y) = (&self.nodes[a], &self.nodes[b]);
+                    if x.cells
+                        .iter()
+                        .all(|&p| y.cells.iter().all(|&q| sudoku.sees(p, q)))
+                    {
+                        self.add_link(a, b, LinkKind::Weak);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Node ids for each value, indexed by `value - 1`
+    fn nodes_by_value(&self, sudoku: &Sudoku) -> Vec<Vec<usize>> {
+        let mut by_value = vec![Vec::new(); sudoku.size];
+        for (id, node) in self.nodes.iter().enumerate() {
+            by_value[node.value as usize - 1].push(id);
+        }
+        by_value
+    }
+}
+
+/// Remove every candidate of a cell other than `keep`
+fn eliminate_others(
+    sudoku: &Sudoku,
+    (row, col): (usize, usize),
+    keep: &[u8],
+    eliminations: &mut Vec<Elimination>,
+) {
+    for value in sudoku.candidate_values(row, col) {
+        if !keep.contains(&value) {
+            eliminations.push(Elimination::new(row, col, value));
+        }
+    }
+}
+
+/// Alternating Inference Chains: chains of alternating strong and weak links between
+/// candidates (including grouped candidates), covering both open chains and nice loops.
+///
+/// - An open chain starting and ending with a strong link proves one of its ends is true.
+/// - A continuous loop turns every weak link into a strong one.
+/// - A loop that closes with two strong (or two weak) links proves its first node true (or false).
+pub struct AlternatingInferenceChains {
+    max_links: usize,
+}
+
+impl AlternatingInferenceChains {
+    pub fn new() -> Self {
+        Self { max_links: 16 }
+    }
+
+    /// Limit the length of the chains searched for
+    pub fn with_max_links(max_links: usize) -> Self {
+        Self { max_links }
+    }
+
+    /// The shortest chain in the grid that removes at least one candidate
+    pub fn find_deduction(&self, sudoku: &Sudoku) -> Option<ChainDeduction> {
+        let graph = InferenceGraph::build(sudoku);
+        let mut best = None;
+
+        for start in 0..graph.nodes.len() {
+            self.search(sudoku, &graph, start, true, &mut best);
+            if !graph.nodes[start].is_group() {
+                self.search(sudoku, &graph, start, false, &mut best);
+            }
+        }
+
+        best
+    }
+
+    /// Breadth-first search for chains starting at `start`, keeping the shortest deduction
+    fn search(
+        &self,
+        sudoku: &Sudoku,
+        graph: &InferenceGraph,
+        start: usize,
+        strong_first: bool,
+        best: &mut Option<ChainDeduction>,
+    ) {
+        // State: (node, whether the next link must be strong)
+        let state = |node: usize, strong_next: bool| node * 2 + strong_next as usize;
+        let mut parent: Vec<Option<usize>> = vec![None; graph.nodes.len() * 2];
+        let mut seen = vec![false; graph.nodes.len() * 2];
+        let path_to = |parent: &[Option<usize>], mut at: usize| {
+            let mut path = vec![at / 2];
+            while let Some(previous) = parent[at] {
+                path.push(previous / 2);
+                at = previous;
+            }
+            path.reverse();
+            path
+        };
+
+        seen[state(start, strong_first)] = true;
+        let mut queue = VecDeque::from([(start, strong_first, 0)]);
+        while let Some((current, strong_next, depth)) = queue.pop_front() {
+            let limit = best
+                .as_ref()
+                .map_or(self.max_links, |b| b.chain.links.len() - 1);
+            if depth >= limit.min(self.max_links) {
+                continue;
+            }
+
+            let neighbours = if strong_next {
+                &graph.strong[current]
+            } else {
+                &graph.weak[current]
+            };
+            for &other in neighbours {
+                let current_state = state(current, strong_next);
+                if other == start {
+                    let mut path = path_to(&parent, current_state);
+                    path.push(start);
+                    self.consider(sudoku, graph, &path, strong_first, true, best);
+                    continue;
+                }
+
+                let next_state = state(other, !strong_next);
+                if seen[next_state] {
+                    continue;
+                }
+                seen[next_state] = true;
+                parent[next_state] = Some(current_state);
+                queue.push_back((other, !strong_next, depth + 1));
+
+                if strong_first && strong_next {
+                    let path = path_to(&parent, next_state);
+                    self.consider(sudoku, graph, &path, strong_first, false, best);
+                }
+            }
+        }
+    }
+
+    /// Work out what a chain proves and keep it if it is shorter than the best so far
+    fn consider(
+        &self,
+        sudoku: &Sudoku,
+        graph: &InferenceGraph,
+        path: &[usize],
+        strong_first: bool,
+        closed: bool,
+        best: &mut Option<ChainDeduction>,
+    ) {
+        let link_count = path.len() - 1;
+        let body = if closed { &path[..link_count] } else { path };
+        let mut unique = body.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        if link_count < 3 || unique.len() != body.len() {
+            return;
+        }
+        if best
+            .as_ref()
+            .is_some_and(|b| b.chain.links.len() <= link_count)
+        {
+            return;
+        }
+
+        let links: Vec<LinkKind> = (0..link_count)
+            .map(|i| {
+                if (i % 2 == 0) == strong_first {
+                    LinkKind::Strong
+                } else {
+                    LinkKind::Weak
+                }
+            })
+            .collect();
+        let nodes: Vec<&ChainNode> = path.iter().map(|&i| &graph.nodes[i]).collect();
+        let (first, last) = (nodes[0], nodes[link_count]);
+        let closing = links[link_count - 1];
+        let mut eliminations = Vec::new();
+        let mut is_loop = false;
+
+        if !closed {
+            // At least one end is true
+            if first.value == last.value {
+                let cells: Vec<(usize, usize)> =
+                    first.cells.iter().chain(&last.cells).copied().collect();
+                eliminate_from_common_peers(sudoku, &cells, first.value, &mut eliminations);
+            } else if !first.is_group() && !last.is_group() {
+                let (a, b) = (first.cells[0], last.cells[0]);
+                if a == b {
+                    eliminate_others(sudoku, a, &[first.value, last.value], &mut eliminations);
+                } else if sudoku.sees(a, b) {
+                    if sudoku.has_candidate(a.0, a.1, last.value) {
+                        eliminations.push(Elimination::new(a.0, a.1, last.value));
+                    }
+                    if sudoku.has_candidate(b.0, b.1, first.value) {
+                        eliminations.push(Elimination::new(b.0, b.1, first.value));
+                    }
+                }
+            }
+        } else if strong_first && closing == LinkKind::Weak {
+            if link_count < 4 {
+                return;
+            }
+            // Continuous loop: every weak link is also strong
+            is_loop = true;
+            for (i, link) in links.iter().enumerate() {
+                if *link != LinkKind::Weak {
+                    continue;
+                }
+                let (a, b) = (nodes[i], nodes[i + 1]);
+                if a.value == b.value {
+                    let cells: Vec<(usize, usize)> =
+                        a.cells.iter().chain(&b.cells).copied().collect();
+                    eliminate_from_common_peers(sudoku, &cells, a.value, &mut eliminations);
+                } else if !a.is_group() && a.cells == b.cells {
+                    eliminate_others(sudoku, a.cells[0], &[a.value, b.value], &mut eliminations);
+                }
+            }
+        } else if strong_first && closing == LinkKind::Strong {
+            // Assuming the first node false makes it true
+            if first.is_group() {
+                eliminate_from_common_peers(sudoku, &first.cells, first.value, &mut eliminations);
+            } else {
+                eliminate_others(sudoku, first.cells[0], &[first.value], &mut eliminations);
+            }
+        } else if !strong_first && closing == LinkKind::Weak {
+            // Assuming the first node true makes it false
+            let (row, col) = first.cells[0];
+            eliminations.push(Elimination::new(row, col, first.value));
+        } else {
+            return;
+        }
+
+        dedup_eliminations(&mut eliminations);
+        if !eliminations.is_empty() {
+            *best = Some(ChainDeduction {
+                chain: Chain {
+                    nodes: nodes.into_iter().cloned().collect(),
+                    links,
+                    is_loop,
+                },
+                eliminations,
+            });
+        }
+    }
+}
+
+impl Default for AlternatingInferenceChains {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SolvingStrategy for AlternatingInferenceChains {
+    fn name(&self) -> &'static str {
+        "Alternating Inference Chain"
+    }
+
+    fn rating(&self) -> f32 {
+        7.0
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find_deduction(sudoku)
+            .map(|deduction| {
+                let mut cells = Vec::new();
+                for node in &deduction.chain.nodes {
+                    for &cell in &node.cells {
+                        if !cells.contains(&cell) {
+                            cells.push(cell);
+                        }
+                    }
+                }
+                Step::elimination(self.name(), deduction.eliminations)
+                    .with_cells(cells)
+                    .with_explanation(deduction.chain.explain())
+            })
+            .into_iter()
+            .collect()
+    }
+}
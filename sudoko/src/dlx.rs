@@ -0,0 +1,412 @@
+//! Dancing Links: Knuth's Algorithm X for exact cover, applied to the empty cells of a grid.
+//!
+//! Each placement (row, column, value) is a row of the exact-cover matrix, covering the
+//! constraints that the cell holds a value and that each house of the cell holds the value.
+//! Constraints already met by the placed values are left out of the matrix. Two neighbouring
+//! cells that must differ without sharing a house give secondary constraints, one per
+//! value, that may be met at most once rather than exactly once. Killer cage sums are not
+//! constraints of the matrix; instead a placement is skipped when the rest of its cage
+//! could no longer make the cage's sum.
+
+use crate::killer::sum_range;
+use crate::sudoku::{full_mask, mask_values, value_bit, Sudoku};
+use std::time::Instant;
+
+/// A placement: row, column and value
+type Choice = (usize, usize, u8);
+
+pub(crate) struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of each node
+    column: Vec<usize>,
+    /// Index into `choices` of each node
+    row: Vec<usize>,
+    /// Number of nodes in each column
+    sizes: Vec<usize>,
+    choices: Vec<Choice>,
+    /// Placements tried so far
+    nodes: usize,
+    deadline: Option<Instant>,
+    node_limit: Option<usize>,
+    stopped: bool,
+    size: usize,
+    /// Cage of each cell, if any, in row-major order
+    cell_cages: Vec<Option<usize>>,
+    cages: Vec<CageTotal>,
+}
+
+/// What a Killer cage holds so far during a search
+struct CageTotal {
+    sum: u32,
+    /// What the values placed add up to
+    total: u32,
+    /// Values placed
+    used: u32,
+    /// Empty cells left
+    left: usize,
+}
+
+/// Index of the root header; column headers follow it
+const ROOT: usize = 0;
+
+impl DancingLinks {
+    /// The exact-cover matrix for the empty cells of a valid grid, limited to their pencil marks
+    pub(crate) fn new(sudoku: &Sudoku) -> Self {
+        let size = sudoku.size;
+        let primary = size * size + sudoku.layout().units().len() * size;
+        let pairs = Pairs::new(sudoku);
+        let constraints = primary + pairs.count * size;
+
+        // Constraint ids: cell, then unit/value for each unit in layout order
+        let mut satisfied = vec![false; constraints];
+        for row in 0..size {
+            for col in 0..size {
+                if let Some(value) = sudoku.cell(row, col).value() {
+                    for id in Self::constraints(sudoku, row, col, value) {
+                        satisfied[id] = true;
+                    }
+                }
+            }
+        }
+        let mut header = vec![0; constraints];
+        let mut columns = 0;
+        for (id, &done) in satisfied.iter().enumerate().take(primary) {
+            if !done {
+                columns += 1;
+                header[id] = columns;
+            }
+        }
+        // Secondary headers come after the primary ones, outside the list the search
+        // picks constraints from
+        for (i, id) in header[primary..].iter_mut().enumerate() {
+            *id = columns + 1 + i;
+        }
+        let headers = columns + 1 + pairs.count * size;
+
+        let mut links = Self {
+            left: (0..headers)
+                .map(|i| match i {
+                    0 => columns,
+                    i if i <= columns => i - 1,
+                    i => i,
+                })
+                .collect(),
+            right: (0..headers)
+                .map(|i| match i {
+                    i if i == columns => 0,
+                    i if i < columns => i + 1,
+                    i => i,
+                })
+                .collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            sizes: vec![0; headers],
+            choices: Vec::new(),
+            nodes: 0,
+            deadline: None,
+            node_limit: None,
+            stopped: false,
+            size,
+            cell_cages: (0..size * size)
+                .map(|cell| sudoku.layout().cage_index(cell / size, cell % size))
+                .collect(),
+            cages: sudoku
+                .layout()
+                .cages()
+                .iter()
+                .map(|cage| {
+                    let placed: Vec<u8> = cage
+                        .cells
+                        .iter()
+                        .filter_map(|&(row, col)| sudoku.cell(row, col).value())
+                        .collect();
+                    CageTotal {
+                        sum: cage.sum,
+                        total: placed.iter().map(|&value| u32::from(value)).sum(),
+                        used: placed
+                            .iter()
+                            .fold(0, |mask, &value| mask | value_bit(value)),
+                        left: cage.cells.len() - placed.len(),
+                    }
+                })
+                .collect(),
+        };
+
+        for row in 0..size {
+            for col in 0..size {
+                let mask = sudoku.candidate_mask(row, col)
+                    & sudoku.allowed_mask(row, col)
+                    & sudoku.cage_mask(row, col);
+                for value in mask_values(mask) {
+                    let headers: Vec<usize> = Self::constraints(sudoku, row, col, value)
+                        .chain(pairs.constraints(sudoku, row, col, value, primary))
+                        .map(|id| header[id])
+                        .collect();
+                    links.add_row((row, col, value), &headers);
+                }
+            }
+        }
+
+        links
+    }
+
+    /// The constraint ids met by placing `value` at (`row`, `col`)
+    fn constraints(
+        sudoku: &Sudoku,
+        row: usize,
+        col: usize,
+        value: u8,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let size = sudoku.size;
+        let cell = row * size + col;
+        let value = value as usize - 1;
+        std::iter::once(cell).chain(
+            sudoku
+                .layout()
+                .units_of(cell)
+                .iter()
+                .map(move |&unit| size * size + unit * size + value),
+        )
+    }
+
+    fn add_row(&mut self, choice: Choice, headers: &[usize]) {
+        let index = self.choices.len();
+        self.choices.push(choice);
+
+        let first = self.column.len();
+        let last_node = first + headers.len() - 1;
+        for (i, &header) in headers.iter().enumerate() {
+            let node = first + i;
+            self.left.push(if i == 0 { last_node } else { node - 1 });
+            self.right
+                .push(if node == last_node { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.column.push(header);
+            self.row.push(index);
+
+            let last = self.up[header];
+            self.down[last] = node;
+            self.up[header] = node;
+            self.sizes[header] += 1;
+        }
+    }
+
+    /// Stop searching once `deadline` has passed
+    pub(crate) fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Stop searching once `limit` placements have been tried
+    pub(crate) fn set_node_limit(&mut self, limit: Option<usize>) {
+        self.node_limit = limit;
+    }
+
+    /// Whether a search was stopped by the deadline or the node limit
+    pub(crate) fn stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Number of placements tried by searches so far
+    pub(crate) fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Search for exact covers, calling `found` with the placements of each one until it
+    /// returns `false`. Returns `false` if the search was stopped early.
+    pub(crate) fn search(&mut self, found: &mut dyn FnMut(&[Choice]) -> bool) -> bool {
+        let mut solution = Vec::new();
+        self.search_from(&mut solution, found)
+    }
+
+    fn search_from(
+        &mut self,
+        solution: &mut Vec<usize>,
+        found: &mut dyn FnMut(&[Choice]) -> bool,
+    ) -> bool {
+        if self.right[ROOT] == ROOT {
+            let choices: Vec<Choice> = solution.iter().map(|&row| self.choices[row]).collect();
+            return found(&choices);
+        }
+
+        // Branch on the constraint with the fewest ways left to meet it
+        let mut header = self.right[ROOT];
+        let mut next = self.right[header];
+        while next != ROOT {
+            if self.sizes[next] < self.sizes[header] {
+                header = next;
+            }
+            next = self.right[next];
+        }
+        if self.sizes[header] == 0 {
+            return true;
+        }
+
+        self.cover(header);
+        let mut keep_going = true;
+        let mut node = self.down[header];
+        while node != header && keep_going {
+            let choice = self.choices[self.row[node]];
+            if !self.cage_allows(choice) {
+                node = self.down[node];
+                continue;
+            }
+            if self.node_limit.is_some_and(|limit| self.nodes >= limit) {
+                self.stopped = true;
+                break;
+            }
+            self.nodes += 1;
+            // Check the clock on the first placement and every 1024 after that
+            if self.nodes % 1024 == 1 && self.deadline.is_some_and(|d| Instant::now() >= d) {
+                self.stopped = true;
+                break;
+            }
+            solution.push(self.row[node]);
+            self.place_in_cage(choice, true);
+            let mut other = self.right[node];
+            while other != node {
+                self.cover(self.column[other]);
+                other = self.right[other];
+            }
+
+            keep_going = self.search_from(solution, found);
+
+            let mut other = self.left[node];
+            while other != node {
+                self.uncover(self.column[other]);
+                other = self.left[other];
+            }
+            self.place_in_cage(choice, false);
+            solution.pop();
+            node = self.down[node];
+        }
+        self.uncover(header);
+
+        keep_going && !self.stopped
+    }
+
+    /// Whether the rest of the placement's cage, if any, could still make the cage's sum
+    /// with different values after it
+    fn cage_allows(&self, (row, col, value): Choice) -> bool {
+        let cage = match self.cell_cages[row * self.size + col] {
+            Some(cage) => &self.cages[cage],
+            None => return true,
+        };
+        let bit = value_bit(value);
+        let total = cage.total + u32::from(value);
+        let free = full_mask(self.size) & !(cage.used | bit);
+        match sum_range(free, cage.left - 1) {
+            Some((low, high)) => {
+                cage.used & bit == 0 && total + low <= cage.sum && total + high >= cage.sum
+            }
+            None => false,
+        }
+    }
+
+    /// Add the placement to its cage's totals, or take it back out
+    fn place_in_cage(&mut self, (row, col, value): Choice, placing: bool) {
+        if let Some(cage) = self.cell_cages[row * self.size + col] {
+            let cage = &mut self.cages[cage];
+            if placing {
+                cage.total += u32::from(value);
+                cage.used |= value_bit(value);
+                cage.left -= 1;
+            } else {
+                cage.total -= u32::from(value);
+                cage.used &= !value_bit(value);
+                cage.left += 1;
+            }
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+
+        let mut node = self.down[header];
+        while node != header {
+            let mut other = self.right[node];
+            while other != node {
+                let (up, down) = (self.up[other], self.down[other]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.sizes[self.column[other]] -= 1;
+                other = self.right[other];
+            }
+            node = self.down[node];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut node = self.up[header];
+        while node != header {
+            let mut other = self.left[node];
+            while other != node {
+                let (up, down) = (self.up[other], self.down[other]);
+                self.down[up] = other;
+                self.up[down] = other;
+                self.sizes[self.column[other]] += 1;
+                other = self.left[other];
+            }
+            node = self.up[node];
+        }
+
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+}
+
+/// Numbers the pairs of neighbouring cells, each counted once from its first cell
+struct Pairs {
+    /// Number of the first pair of each cell
+    first: Vec<usize>,
+    count: usize,
+}
+
+impl Pairs {
+    fn new(sudoku: &Sudoku) -> Self {
+        let layout = sudoku.layout();
+        let mut first = Vec::with_capacity(sudoku.size * sudoku.size);
+        let mut count = 0;
+        for cell in 0..sudoku.size * sudoku.size {
+            first.push(count);
+            count += layout
+                .neighbours_of(cell)
+                .iter()
+                .filter(|&&other| other > cell)
+                .count();
+        }
+        Self { first, count }
+    }
+
+    /// The secondary constraint ids met by placing `value` at (`row`, `col`), numbered
+    /// from `start`: one for each pair the cell is in
+    fn constraints<'a>(
+        &'a self,
+        sudoku: &'a Sudoku,
+        row: usize,
+        col: usize,
+        value: u8,
+        start: usize,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let size = sudoku.size;
+        let layout = sudoku.layout();
+        let cell = row * size + col;
+        let value = value as usize - 1;
+        layout.neighbours_of(cell).iter().map(move |&other| {
+            let (a, b) = (cell.min(other), cell.max(other));
+            // Position of b among the neighbours of a that come after it
+            let later = layout.neighbours_of(a);
+            let offset = later.partition_point(|&n| n <= a);
+            let pair = self.first[a] + later.binary_search(&b).unwrap() - offset;
+            start + pair * size + value
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned when parsing, editing, solving or generating puzzles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SudokuError {
+    /// The grid size does not split into boxes, or is too large
+    InvalidSize(usize),
+    /// The puzzle text does not have one character per cell
+    InvalidLength { expected: usize, found: usize },
+    /// A character in the puzzle text that is not a value for the grid size
+    InvalidCharacter {
+        row: usize,
+        col: usize,
+        character: char,
+    },
+    /// A value larger than the grid size
+    ValueOutOfRange { value: u8, size: usize },
+    /// A position outside the grid
+    InvalidPosition { row: usize, col: usize },
+    /// The givens break the rules; lists every cell sharing its value with another in a unit
+    ContradictoryGivens { cells: Vec<(usize, usize)> },
+    /// The puzzle has no solution
+    Unsolvable,
+    /// The logical strategies hit the iteration limit without solving the puzzle
+    IterationLimit(usize),
+    /// The solver ran out of time
+    TimeLimit(Duration),
+    /// A puzzle could not be generated
+    GenerationFailed(String),
+    /// Houses that do not fit together into a grid
+    InvalidLayout(String),
+    /// A difficulty name other than easy, medium, hard or expert
+    InvalidDifficulty(String),
+    /// A technique name that matches none of the solver's strategies
+    UnknownTechnique(String),
+    /// A variant name other than diagonal, windoku, anti-knight or anti-king
+    UnknownVariant(String),
+    /// A Killer cage that cannot be read or does not fit the grid
+    InvalidCage(String),
+    /// Reading a puzzle failed
+    Io(String),
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SudokuError::InvalidSize(size) => {
+                write!(f, "Invalid Sudoku size: {} does not split into boxes", size)
+            }
+            SudokuError::InvalidLength { expected, found } => write!(
+                f,
+                "Invalid input length: expected {}, got {}",
+                expected, found
+            ),
+            SudokuError::InvalidCharacter {
+                row,
+                col,
+                character,
+            } => write!(
+                f,
+                "Invalid character '{}' at r{}c{}",
+                character,
+                row + 1,
+                col + 1
+            ),
+            SudokuError::ValueOutOfRange { value, size } => {
+                write!(
+                    f,
+                    "Value {} is too large for {}x{} Sudoku",
+                    value, size, size
+                )
+            }
+            SudokuError::InvalidPosition { row, col } => {
+                write!(f, "Invalid position ({}, {})", row, col)
+            }
+            SudokuError::ContradictoryGivens { cells } => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|&(row, col)| format!("r{}c{}", row + 1, col + 1))
+                    .collect();
+                write!(f, "Contradictory givens at {}", cells.join(", "))
+            }
+            SudokuError::Unsolvable => write!(f, "No solution found"),
+            SudokuError::IterationLimit(limit) => {
+                write!(f, "Gave up after {} iterations", limit)
+            }
+            SudokuError::TimeLimit(limit) => write!(f, "Gave up after {:?}", limit),
+            SudokuError::GenerationFailed(reason) => {
+                write!(f, "Failed to generate puzzle: {}", reason)
+            }
+            SudokuError::InvalidLayout(reason) => write!(f, "Invalid layout: {}", reason),
+            SudokuError::InvalidDifficulty(name) => write!(
+                f,
+                "Invalid difficulty: {}. Use easy, medium, hard, or expert",
+                name
+            ),
+            SudokuError::UnknownTechnique(name) => write!(f, "Unknown technique: {}", name),
+            SudokuError::UnknownVariant(name) => write!(
+                f,
+                "Unknown variant: {}. Use diagonal, windoku, anti-knight or anti-king",
+                name
+            ),
+            SudokuError::InvalidCage(reason) => write!(f, "Invalid cage: {}", reason),
+            SudokuError::Io(message) => write!(f, "Error reading file: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
+impl From<std::io::Error> for SudokuError {
+    fn from(error: std::io::Error) -> Self {
+        SudokuError::Io(error.to_string())
+    }
+}
@@ -0,0 +1,340 @@
+//! Basic and finned fish (X-Wing, Swordfish, Jellyfish and larger) for a single value
+
+use crate::strategies::{for_each_combination, push_step, Elimination, SolvingStrategy, Step};
+use crate::sudoku::{Sudoku, Unit, UnitKind};
+
+/// A fish of any order: `order` base lines whose candidates for a value are covered by
+/// `order` cross lines, so the value can be removed from the rest of the cover lines.
+///
+/// Finned fish allow extra candidates ("fins") in the base lines as long as they all sit
+/// in one box; eliminations are then limited to cover cells inside that box. This also
+/// covers sashimi fish, where a base line holds fewer candidates than the plain pattern.
+#[derive(Debug, Clone, Copy)]
+pub struct Fish {
+    order: usize,
+    finned: bool,
+}
+
+impl Fish {
+    /// A basic fish with `order` base lines (2 = X-Wing, 3 = Swordfish, 4 = Jellyfish)
+    pub fn new(order: usize) -> Self {
+        Self {
+            order,
+            finned: false,
+        }
+    }
+
+    /// A finned (or sashimi) fish with `order` base lines
+    pub fn finned(order: usize) -> Self {
+        Self {
+            order,
+            finned: true,
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn is_finned(&self) -> bool {
+        self.finned
+    }
+
+    /// A step for every fish of this order and kind in the grid
+    pub fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+
+        // A fish of order n has a complementary fish of order size - n, so larger ones add nothing
+        if self.order < 2 || self.order * 2 > sudoku.size {
+            return steps;
+        }
+
+        for value in 1..=sudoku.size as u8 {
+            for rows_as_base in [true, false] {
+                let cell = |base: usize, cross: usize| {
+                    if rows_as_base {
+                        (base, cross)
+                    } else {
+                        (cross, base)
+                    }
+                };
+                let (base_kind, cover_kind) = if rows_as_base {
+                    (UnitKind::Row, UnitKind::Column)
+                } else {
+                    (UnitKind::Column, UnitKind::Row)
+                };
+
+                // Cross positions of the value in each base line
+                let lines: Vec<(usize, u32)> = (0..sudoku.size)
+                    .map(|base| {
+                        let mask = (0..sudoku.size)
+                            .filter(|&cross| {
+                                let (row, col) = cell(base, cross);
+                                sudoku.has_candidate(row, col, value)
+                            })
+                            .fold(0u32, |mask, cross| mask | (1 << cross));
+                        (base, mask)
+                    })
+                    .filter(|&(_, mask)| mask != 0)
+                    .collect();
+
+                let max_union = if self.finned {
+                    self.order + box_width(sudoku, rows_as_base)
+                } else {
+                    self.order
+                };
+
+                let mut base = Vec::with_capacity(self.order);
+                self.search(&lines, 0, 0, max_union, &mut base, &mut |base, union| {
+                    let found = if self.finned {
+                        self.finned_eliminations(sudoku, value, base, union, &cell)
+                    } else {
+                        self.basic_eliminations(sudoku, value, base, union, &cell)
+                    };
+
+                    for (cover, eliminations) in found {
+                        let cells = base
+                            .iter()
+                            .flat_map(|&line| (0..sudoku.size).map(move |cross| cell(line, cross)))
+                            .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+                            .collect();
+                        let units = base
+                            .iter()
+                            .map(|&line| Unit::new(base_kind, line))
+                            .chain(
+                                (0..sudoku.size)
+                                    .filter(|&cross| cover & (1 << cross) != 0)
+                                    .map(|cross| Unit::new(cover_kind, cross)),
+                            )
+                            .collect();
+                        let step = Step::elimination(self.name(), eliminations)
+                            .with_cells(cells)
+                            .with_units(units);
+                        push_step(&mut steps, step);
+                    }
+                });
+            }
+        }
+
+        steps
+    }
+
+    /// Enumerate sets of `order` base lines whose combined cross positions stay within `max_union`
+    fn search(
+        &self,
+        lines: &[(usize, u32)],
+        start: usize,
+        union: u32,
+        max_union: usize,
+        base: &mut Vec<usize>,
+        found: &mut dyn FnMut(&[usize], u32),
+    ) {
+        if base.len() == self.order {
+            found(base, union);
+            return;
+        }
+
+        for i in start..lines.len() {
+            let (line, mask) = lines[i];
+            let next = union | mask;
+            if next.count_ones() as usize > max_union {
+                continue;
+            }
+            base.push(line);
+            self.search(lines, i + 1, next, max_union, base, found);
+            base.pop();
+        }
+    }
+
+    /// Eliminations made by a fish, along with its cover lines
+    fn basic_eliminations(
+        &self,
+        sudoku: &Sudoku,
+        value: u8,
+        base: &[usize],
+        union: u32,
+        cell: &dyn Fn(usize, usize) -> (usize, usize),
+    ) -> Vec<(u32, Vec<Elimination>)> {
+        let mut eliminations = Vec::new();
+        if union.count_ones() as usize != self.order {
+            return Vec::new();
+        }
+
+        for cross in (0..sudoku.size).filter(|&cross| union & (1 << cross) != 0) {
+            for line in (0..sudoku.size).filter(|line| !base.contains(line)) {
+                let (row, col) = cell(line, cross);
+                if sudoku.has_candidate(row, col, value) {
+                    eliminations.push(Elimination::new(row, col, value));
+                }
+            }
+        }
+
+        vec![(union, eliminations)]
+    }
+
+    fn finned_eliminations(
+        &self,
+        sudoku: &Sudoku,
+        value: u8,
+        base: &[usize],
+        union: u32,
+        cell: &dyn Fn(usize, usize) -> (usize, usize),
+    ) -> Vec<(u32, Vec<Elimination>)> {
+        let mut found = Vec::new();
+        let positions: Vec<usize> = (0..sudoku.size)
+            .filter(|&cross| union & (1 << cross) != 0)
+            .collect();
+        if positions.len() <= self.order {
+            return found;
+        }
+
+        for_each_combination(positions.len(), self.order, |combo| {
+            let cover: u32 = combo.iter().fold(0, |mask, &i| mask | (1 << positions[i]));
+
+            // Base candidates outside the cover lines are fins, and must share one box
+            let fins: Vec<(usize, usize)> = base
+                .iter()
+                .flat_map(|&line| {
+                    positions
+                        .iter()
+                        .filter(move |&&cross| cover & (1 << cross) == 0)
+                        .map(move |&cross| cell(line, cross))
+                })
+                .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+                .collect();
+            let fin_box = match fins.first() {
+                Some(&(row, col)) => sudoku.box_index(row, col),
+                None => return,
+            };
+            if fins
+                .iter()
+                .any(|&(row, col)| sudoku.box_index(row, col) != fin_box)
+            {
+                return;
+            }
+
+            let mut eliminations = Vec::new();
+            for &cross in positions.iter().filter(|&&cross| cover & (1 << cross) != 0) {
+                for line in (0..sudoku.size).filter(|line| !base.contains(line)) {
+                    let (row, col) = cell(line, cross);
+                    if sudoku.box_index(row, col) == fin_box
+                        && sudoku.has_candidate(row, col, value)
+                    {
+                        eliminations.push(Elimination::new(row, col, value));
+                    }
+                }
+            }
+            found.push((cover, eliminations));
+        });
+
+        found
+    }
+}
+
+/// The most cross lines one box spans, so the most extra positions fins in a box can add
+fn box_width(sudoku: &Sudoku, rows_as_base: bool) -> usize {
+    let mut crosses = vec![0u32; sudoku.size];
+    for row in 0..sudoku.size {
+        for col in 0..sudoku.size {
+            let cross = if rows_as_base { col } else { row };
+            crosses[sudoku.box_index(row, col)] |= 1 << cross;
+        }
+    }
+    crosses
+        .iter()
+        .map(|mask| mask.count_ones() as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+impl SolvingStrategy for Fish {
+    fn name(&self) -> &'static str {
+        match (self.order, self.finned) {
+            (2, false) => "X-Wing",
+            (3, false) => "Swordfish",
+            (4, false) => "Jellyfish",
+            (5, false) => "Squirmbag",
+            (6, false) => "Whale",
+            (7, false) => "Leviathan",
+            (_, false) => "Fish",
+            (2, true) => "Finned X-Wing",
+            (3, true) => "Finned Swordfish",
+            (4, true) => "Finned Jellyfish",
+            (5, true) => "Finned Squirmbag",
+            (6, true) => "Finned Whale",
+            (7, true) => "Finned Leviathan",
+            (_, true) => "Finned Fish",
+        }
+    }
+
+    fn rating(&self) -> f32 {
+        let rating = match self.order {
+            2 => 3.2,
+            3 => 3.8,
+            4 => 5.2,
+            5 => 6.0,
+            6 => 6.4,
+            _ => 6.8,
+        };
+        if self.finned {
+            rating + 0.2
+        } else {
+            rating
+        }
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
+
+/// X-Wing: Two rows (or columns) where a value is confined to the same two columns (or rows)
+pub struct XWing;
+
+impl SolvingStrategy for XWing {
+    fn name(&self) -> &'static str {
+        "X-Wing"
+    }
+
+    fn rating(&self) -> f32 {
+        3.2
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        Fish::new(2).find(sudoku)
+    }
+}
+
+/// Swordfish: The three-line version of the X-Wing
+pub struct Swordfish;
+
+impl SolvingStrategy for Swordfish {
+    fn name(&self) -> &'static str {
+        "Swordfish"
+    }
+
+    fn rating(&self) -> f32 {
+        3.8
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        Fish::new(3).find(sudoku)
+    }
+}
+
+/// Jellyfish: The four-line version of the X-Wing
+pub struct Jellyfish;
+
+impl SolvingStrategy for Jellyfish {
+    fn name(&self) -> &'static str {
+        "Jellyfish"
+    }
+
+    fn rating(&self) -> f32 {
+        5.2
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        Fish::new(4).find(sudoku)
+    }
+}
@@ -0,0 +1,170 @@
+//! Options controlling the puzzles the generator makes
+
+use crate::error::SudokuError;
+use crate::layout::Layout;
+use crate::solver::Difficulty;
+use crate::variant::Variant;
+
+/// Which cells are emptied together, so the clues of a generated puzzle form a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    /// Cells are emptied one at a time
+    None,
+    /// The pattern looks the same turned half way round
+    #[default]
+    Rotational180,
+    /// The pattern looks the same turned a quarter of the way round
+    Rotational90,
+    /// The pattern looks the same flipped top to bottom
+    HorizontalMirror,
+    /// The pattern looks the same flipped left to right
+    VerticalMirror,
+    /// The pattern looks the same flipped across the main diagonal
+    Diagonal,
+    /// The pattern looks the same under every rotation and flip
+    Dihedral,
+}
+
+impl Symmetry {
+    /// The cells that are emptied or kept together with (`row`, `col`), including it, in
+    /// row-major order
+    pub fn orbit(self, row: usize, col: usize, size: usize) -> Vec<(usize, usize)> {
+        let last = size - 1;
+        let quarter_turn = |(row, col): (usize, usize)| (col, last - row);
+
+        let mut cells = vec![(row, col)];
+        match self {
+            Symmetry::None => {}
+            Symmetry::Rotational180 => cells.push((last - row, last - col)),
+            Symmetry::Rotational90 | Symmetry::Dihedral => {
+                for _ in 0..3 {
+                    let next = quarter_turn(cells[cells.len() - 1]);
+                    cells.push(next);
+                }
+                if self == Symmetry::Dihedral {
+                    let flipped: Vec<(usize, usize)> =
+                        cells.iter().map(|&(row, col)| (col, row)).collect();
+                    cells.extend(flipped);
+                }
+            }
+            Symmetry::HorizontalMirror => cells.push((last - row, col)),
+            Symmetry::VerticalMirror => cells.push((row, last - col)),
+            Symmetry::Diagonal => cells.push((col, row)),
+        }
+
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+/// What a generated puzzle should look like
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationOptions {
+    pub difficulty: Difficulty,
+    pub symmetry: Symmetry,
+    /// Fewest clues the puzzle may have
+    pub min_clues: Option<usize>,
+    /// Most clues the puzzle may have
+    pub max_clues: Option<usize>,
+    /// Techniques, by strategy name, the puzzle must need when solved easiest step first
+    pub required_techniques: Vec<String>,
+    /// Techniques, by strategy name, the puzzle must be solvable without
+    pub forbidden_techniques: Vec<String>,
+    /// Seed for the random choices; `None` picks a fresh one each time
+    pub seed: Option<u64>,
+    /// Variant rules the puzzle is played with
+    pub variants: Vec<Variant>,
+    /// Largest cage of a Killer puzzle, whose solution is split into random cages that
+    /// stand in for most givens; `None` for no cages
+    pub cage_size: Option<usize>,
+    /// Rows and columns of each box; `None` for the usual shape for the size
+    pub box_shape: Option<(usize, usize)>,
+}
+
+impl GenerationOptions {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            symmetry: Symmetry::default(),
+            min_clues: None,
+            max_clues: None,
+            required_techniques: Vec::new(),
+            forbidden_techniques: Vec::new(),
+            seed: None,
+            variants: Vec::new(),
+            cage_size: None,
+            box_shape: None,
+        }
+    }
+
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Keep the number of clues between `min` and `max`, inclusive
+    pub fn with_clues(mut self, min: usize, max: usize) -> Self {
+        self.min_clues = Some(min);
+        self.max_clues = Some(max);
+        self
+    }
+
+    pub fn with_required_technique(mut self, technique: &str) -> Self {
+        self.required_techniques.push(technique.to_string());
+        self
+    }
+
+    pub fn with_forbidden_technique(mut self, technique: &str) -> Self {
+        self.forbidden_techniques.push(technique.to_string());
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Make a Killer puzzle with cages of up to `max_size` cells
+    pub fn with_killer_cages(mut self, max_size: usize) -> Self {
+        self.cage_size = Some(max_size);
+        self
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        if !self.variants.contains(&variant) {
+            self.variants.push(variant);
+        }
+        self
+    }
+
+    /// Use boxes `box_rows` tall and `box_cols` wide, such as 3x2 on a 6x6 grid
+    pub fn with_box_shape(mut self, box_rows: usize, box_cols: usize) -> Self {
+        self.box_shape = Some((box_rows, box_cols));
+        self
+    }
+
+    /// The houses of a `size` grid with these boxes and variant rules
+    pub(crate) fn layout(&self, size: usize) -> Result<Layout, SudokuError> {
+        let mut layout = match self.box_shape {
+            Some((box_rows, box_cols)) if box_rows * box_cols != size => {
+                return Err(SudokuError::InvalidLayout(format!(
+                    "{}x{} boxes do not fit a {}x{} grid",
+                    box_rows, box_cols, size, size
+                )))
+            }
+            Some((box_rows, box_cols)) => Layout::rectangular(box_rows, box_cols)?,
+            None => Layout::standard(size)?,
+        };
+        for &variant in &self.variants {
+            layout.add_variant(variant)?;
+        }
+        Ok(layout)
+    }
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self::new(Difficulty::Medium)
+    }
+}
@@ -0,0 +1,563 @@
+//! Killer Sudoku: cages of cells whose values add up to a given sum without repeating, the
+//! strategies reasoning about their sums, and the random cages of generated puzzles
+
+use crate::error::SudokuError;
+use crate::strategies::{push_step, Elimination, SolvingStrategy, Step};
+use crate::sudoku::{mask_values, value_bit, Sudoku, Unit, UnitKind};
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Cells whose values add up to `sum` with no value repeated, added to a grid with
+/// `Layout::add_cage`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cage {
+    pub sum: u32,
+    /// The cells, in row-major order once the cage is part of a layout
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Cage {
+    pub fn new(sum: u32, cells: Vec<(usize, usize)>) -> Self {
+        Self { sum, cells }
+    }
+
+    /// Read cages written as `Display` writes them, such as `15: r1c1 r1c2 r2c1`, one per
+    /// line or separated by `;`; blank lines and lines starting with `#` are skipped
+    pub fn parse_list(s: &str) -> Result<Vec<Cage>, SudokuError> {
+        s.lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(|line| line.split(';'))
+            .map(str::trim)
+            .filter(|cage| !cage.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for Cage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.sum)?;
+        for &(row, col) in &self.cells {
+            write!(f, " r{}c{}", row + 1, col + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cage {
+    type Err = SudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sum, cells) = s.split_once(':').ok_or_else(|| {
+            SudokuError::InvalidCage(format!("'{}' has no ':' after its sum", s.trim()))
+        })?;
+        let sum = sum
+            .trim()
+            .parse()
+            .map_err(|_| SudokuError::InvalidCage(format!("'{}' is not a sum", sum.trim())))?;
+        let cells = cells
+            .split(|ch: char| ch.is_whitespace() || ch == ',')
+            .filter(|cell| !cell.is_empty())
+            .map(parse_cell)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Cage::new(sum, cells))
+    }
+}
+
+/// A cell written as `r1c2`, with the row and column counted from 1
+fn parse_cell(text: &str) -> Result<(usize, usize), SudokuError> {
+    let lower = text.to_lowercase();
+    let position = lower
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .and_then(|(row, col)| Some((row.parse::<usize>().ok()?, col.parse::<usize>().ok()?)));
+    match position {
+        Some((row, col)) if row > 0 && col > 0 => Ok((row - 1, col - 1)),
+        _ => Err(SudokuError::InvalidCage(format!(
+            "'{}' is not a cell like r1c2",
+            text
+        ))),
+    }
+}
+
+/// Sum of the values set in a mask
+pub(crate) fn mask_sum(mask: u32) -> u32 {
+    mask_values(mask).map(u32::from).sum()
+}
+
+/// Smallest and largest sums of `count` different values from `free`; `None` if it holds
+/// fewer than `count`
+pub(crate) fn sum_range(free: u32, count: usize) -> Option<(u32, u32)> {
+    let available = free.count_ones() as usize;
+    if available < count {
+        return None;
+    }
+    let low = mask_values(free).take(count).map(u32::from).sum();
+    let high = mask_values(free)
+        .skip(available - count)
+        .map(u32::from)
+        .sum();
+    Some((low, high))
+}
+
+/// Values in some set of `count` different values from `free` adding up to `sum`; zero if
+/// there is none
+pub(crate) fn combination_mask(free: u32, count: usize, sum: u32) -> u32 {
+    combinations(free, count, sum).unwrap_or(0)
+}
+
+/// The union of the sets of `count` values from `free` adding up to `sum`, if any
+fn combinations(free: u32, count: usize, sum: u32) -> Option<u32> {
+    if count == 0 {
+        return (sum == 0).then_some(0);
+    }
+    match sum_range(free, count) {
+        Some((low, high)) if low <= sum && sum <= high => {}
+        _ => return None,
+    }
+
+    // Either the largest value left is in the set or it is not
+    let top = u32::BITS - free.leading_zeros();
+    let bit = 1 << (top - 1);
+    let with = if top <= sum {
+        combinations(free & !bit, count - 1, sum - top).map(|mask| mask | bit)
+    } else {
+        None
+    };
+    match (with, combinations(free & !bit, count, sum)) {
+        (Some(a), Some(b)) => Some(a | b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Ways of filling cells with different values adding up to a sum, each cell keeping to
+/// its mask
+struct Fillings<'a> {
+    masks: &'a [u32],
+    sum: u32,
+    /// Whether the cells from an index on can be filled, given the values used before it
+    memo: HashMap<(usize, u32), bool>,
+    /// Values each cell takes in some filling
+    options: Vec<u32>,
+    /// The set of values of each filling
+    sets: Vec<u32>,
+}
+
+impl<'a> Fillings<'a> {
+    fn find(masks: &'a [u32], sum: u32) -> Self {
+        let mut fillings = Self {
+            masks,
+            sum,
+            memo: HashMap::new(),
+            options: vec![0; masks.len()],
+            sets: Vec::new(),
+        };
+        fillings.fill(0, 0);
+        fillings
+            .sets
+            .sort_by_key(|&set| mask_values(set).collect::<Vec<u8>>());
+        fillings
+    }
+
+    /// Whether the cells from `index` on can be filled after the values in `used`
+    fn fill(&mut self, index: usize, used: u32) -> bool {
+        if index == self.masks.len() {
+            let filled = mask_sum(used) == self.sum;
+            if filled && !self.sets.contains(&used) {
+                self.sets.push(used);
+            }
+            return filled;
+        }
+        if let Some(&filled) = self.memo.get(&(index, used)) {
+            return filled;
+        }
+
+        let total = mask_sum(used);
+        let mut filled = false;
+        for value in mask_values(self.masks[index] & !used) {
+            if total + u32::from(value) > self.sum {
+                break;
+            }
+            if self.fill(index + 1, used | value_bit(value)) {
+                self.options[index] |= value_bit(value);
+                filled = true;
+            }
+        }
+        self.memo.insert((index, used), filled);
+        filled
+    }
+}
+
+/// The sets of values, such as `1+5+9, 2+4+9`
+fn describe_sets(sets: &[u32]) -> String {
+    let sets: Vec<String> = sets
+        .iter()
+        .map(|&set| {
+            let values: Vec<String> = mask_values(set).map(|value| value.to_string()).collect();
+            values.join("+")
+        })
+        .collect();
+    sets.join(", ")
+}
+
+fn describe_cells(cells: &[(usize, usize)]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|&(row, col)| format!("r{}c{}", row + 1, col + 1))
+        .collect();
+    cells.join(", ")
+}
+
+/// Eliminations limiting the empty cells among `cells`, which hold different values, to
+/// the values of the ways they can add up to `sum`; `None` if the placed values already
+/// exceed it. Also returns the sum left for the empty cells and the sets of values that
+/// can make it.
+fn sum_eliminations(
+    sudoku: &Sudoku,
+    cells: &[(usize, usize)],
+    sum: u32,
+) -> Option<(Vec<Elimination>, u32, Vec<u32>)> {
+    let mut placed = 0;
+    let mut empty = Vec::new();
+    for &(row, col) in cells {
+        match sudoku.cell(row, col).value() {
+            Some(value) => placed |= value_bit(value),
+            None => empty.push((row, col)),
+        }
+    }
+    let left = sum.checked_sub(mask_sum(placed))?;
+    if empty.is_empty() {
+        return None;
+    }
+
+    let masks: Vec<u32> = empty
+        .iter()
+        .map(|&(row, col)| sudoku.candidate_mask(row, col) & !placed)
+        .collect();
+    let fillings = Fillings::find(&masks, left);
+    let eliminations = empty
+        .iter()
+        .zip(&fillings.options)
+        .flat_map(|(&(row, col), &options)| {
+            mask_values(sudoku.candidate_mask(row, col) & !options)
+                .map(move |value| Elimination::new(row, col, value))
+        })
+        .collect();
+    Some((eliminations, left, fillings.sets))
+}
+
+/// Cage Combinations: The empty cells of a cage hold different values adding up to what
+/// the placed ones leave of its sum, so candidates in no such set of values, or that no
+/// cell arrangement of one allows, are removed
+pub struct CageCombinations;
+
+impl CageCombinations {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        for (index, cage) in sudoku.layout().cages().iter().enumerate() {
+            let (eliminations, left, sets) = match sum_eliminations(sudoku, &cage.cells, cage.sum) {
+                Some(found) => found,
+                None => continue,
+            };
+            let empty = cage
+                .cells
+                .iter()
+                .filter(|&&(row, col)| sudoku.cell(row, col).is_empty())
+                .count();
+            let step = Step::elimination(self.name(), eliminations)
+                .with_cells(cage.cells.clone())
+                .with_units(vec![Unit::new(UnitKind::Cage, index)])
+                .with_explanation(format!(
+                    "{} in {} cells: {}",
+                    left,
+                    empty,
+                    describe_sets(&sets)
+                ));
+            push_step(&mut steps, step);
+        }
+        steps
+    }
+}
+
+impl SolvingStrategy for CageCombinations {
+    fn name(&self) -> &'static str {
+        "Cage Combinations"
+    }
+
+    fn rating(&self) -> f32 {
+        2.0
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+
+    fn uses_cages(&self) -> bool {
+        true
+    }
+}
+
+/// Innies and Outies, from the 45 rule: every house of a 9x9 grid adds up to 45, or
+/// 1 + 2 + ... + n on an n x n grid.
+///
+/// - Innies: The cells of a house outside the cages lying wholly inside it add up to what
+///   those cages leave of the house total.
+/// - Outies: When cages cover a house, their cells outside it add up to the cage sums less
+///   the house total; used when those cells all see each other, so their values differ.
+///
+/// A lone innie or outie is placed; otherwise the cells are narrowed like a cage.
+pub struct InniesAndOuties;
+
+impl InniesAndOuties {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let layout = sudoku.layout();
+        if layout.cages().is_empty() {
+            return steps;
+        }
+        let size = sudoku.size;
+        let total = (size * (size + 1) / 2) as u32;
+
+        for &unit in layout.units() {
+            let cells = layout.unit_cells(unit);
+            let mut cages: Vec<usize> = cells
+                .iter()
+                .filter_map(|&(row, col)| layout.cage_index(row, col))
+                .collect();
+            cages.sort_unstable();
+            cages.dedup();
+            if cages.is_empty() {
+                continue;
+            }
+
+            let mut inside = 0;
+            let mut covered = Vec::new();
+            let mut over = 0;
+            let mut outies = Vec::new();
+            for &index in &cages {
+                let cage = &layout.cages()[index];
+                if cage.cells.iter().all(|cell| cells.contains(cell)) {
+                    inside += cage.sum;
+                    covered.extend(cage.cells.iter().copied());
+                }
+                over += cage.sum;
+                outies.extend(cage.cells.iter().filter(|cell| !cells.contains(cell)));
+            }
+
+            let innies: Vec<(usize, usize)> = cells
+                .iter()
+                .copied()
+                .filter(|cell| !covered.contains(cell))
+                .collect();
+            if !covered.is_empty() && !innies.is_empty() && inside <= total {
+                let explanation = format!(
+                    "the cages inside {} add up to {}, leaving {} for {}",
+                    unit,
+                    inside,
+                    total - inside,
+                    describe_cells(&innies)
+                );
+                self.push_sum(
+                    sudoku,
+                    unit,
+                    &innies,
+                    total - inside,
+                    explanation,
+                    &mut steps,
+                );
+            }
+
+            let all_caged = cells
+                .iter()
+                .all(|&(row, col)| layout.cage_index(row, col).is_some());
+            let see_each_other = outies
+                .iter()
+                .enumerate()
+                .all(|(i, &a)| outies[i + 1..].iter().all(|&b| sudoku.sees(a, b)));
+            if all_caged && !outies.is_empty() && see_each_other && over >= total {
+                outies.sort_unstable();
+                let explanation = format!(
+                    "the cages covering {} add up to {}, so {} outside it add up to {}",
+                    unit,
+                    over,
+                    describe_cells(&outies),
+                    over - total
+                );
+                self.push_sum(sudoku, unit, &outies, over - total, explanation, &mut steps);
+            }
+        }
+        steps
+    }
+
+    /// Place a lone empty cell of `cells`, which add up to `sum`, or narrow them to the
+    /// values adding up to it
+    fn push_sum(
+        &self,
+        sudoku: &Sudoku,
+        unit: Unit,
+        cells: &[(usize, usize)],
+        sum: u32,
+        explanation: String,
+        steps: &mut Vec<Step>,
+    ) {
+        let empty: Vec<(usize, usize)> = cells
+            .iter()
+            .copied()
+            .filter(|&(row, col)| sudoku.cell(row, col).is_empty())
+            .collect();
+        let placed: u32 = cells
+            .iter()
+            .filter_map(|&(row, col)| sudoku.cell(row, col).value())
+            .map(u32::from)
+            .sum();
+
+        let step = if let [(row, col)] = empty[..] {
+            let value = match sum.checked_sub(placed) {
+                Some(value) if value <= sudoku.size as u32 => value as u8,
+                _ => return,
+            };
+            if !sudoku.has_candidate(row, col, value) {
+                return;
+            }
+            Step::placement(self.name(), row, col, value)
+        } else {
+            match sum_eliminations(sudoku, cells, sum) {
+                Some((eliminations, _, _)) => {
+                    Step::elimination(self.name(), eliminations).with_cells(cells.to_vec())
+                }
+                None => return,
+            }
+        };
+        push_step(
+            steps,
+            step.with_units(vec![unit]).with_explanation(explanation),
+        );
+    }
+}
+
+impl SolvingStrategy for InniesAndOuties {
+    fn name(&self) -> &'static str {
+        "Innies and Outies"
+    }
+
+    fn rating(&self) -> f32 {
+        3.0
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+
+    fn uses_cages(&self) -> bool {
+        true
+    }
+}
+
+/// Split a solved grid into random cages of up to `max_size` cells joined edge to edge,
+/// none repeating a value, in row-major order of their first cells
+pub(crate) fn random_cages<R: Rng + ?Sized>(
+    solution: &Sudoku,
+    max_size: usize,
+    rng: &mut R,
+) -> Vec<Cage> {
+    let size = solution.size;
+    let value = |cell: usize| solution.cell(cell / size, cell % size).value();
+    let edge_neighbours = |cell: usize| {
+        let (row, col) = (cell / size, cell % size);
+        [
+            (row > 0).then(|| cell - size),
+            (row + 1 < size).then(|| cell + size),
+            (col > 0).then(|| cell - 1),
+            (col + 1 < size).then(|| cell + 1),
+        ]
+        .into_iter()
+        .flatten()
+    };
+
+    let mut cage_of: Vec<Option<usize>> = vec![None; size * size];
+    let mut cages: Vec<Vec<usize>> = Vec::new();
+    let mut starts: Vec<usize> = (0..size * size).collect();
+    starts.shuffle(rng);
+    for start in starts {
+        if cage_of[start].is_some() {
+            continue;
+        }
+        let target = if max_size > 1 {
+            rng.gen_range(2..=max_size)
+        } else {
+            1
+        };
+        let id = cages.len();
+        let mut cells = vec![start];
+        cage_of[start] = Some(id);
+        while cells.len() < target {
+            let mut frontier: Vec<usize> = cells
+                .iter()
+                .flat_map(|&cell| edge_neighbours(cell))
+                .filter(|&next| {
+                    cage_of[next].is_none() && cells.iter().all(|&cell| value(cell) != value(next))
+                })
+                .collect();
+            frontier.sort_unstable();
+            frontier.dedup();
+            match frontier.choose(rng) {
+                Some(&next) => {
+                    cage_of[next] = Some(id);
+                    cells.push(next);
+                }
+                None => break,
+            }
+        }
+        cages.push(cells);
+    }
+
+    // A cell left on its own gives its value away, so it joins a neighbouring cage without
+    // that value where one has room
+    for id in 0..cages.len() {
+        if max_size < 2 || cages[id].len() != 1 {
+            continue;
+        }
+        let cell = cages[id][0];
+        let mut joinable: Vec<usize> = edge_neighbours(cell)
+            .filter_map(|next| cage_of[next])
+            .filter(|&other| {
+                other != id
+                    && cages[other].len() < max_size
+                    && cages[other].iter().all(|&c| value(c) != value(cell))
+            })
+            .collect();
+        joinable.sort_unstable();
+        joinable.dedup();
+        if let Some(&other) = joinable.choose(rng) {
+            cages[other].push(cell);
+            cages[id].clear();
+            cage_of[cell] = Some(other);
+        }
+    }
+
+    let mut cages: Vec<Cage> = cages
+        .into_iter()
+        .filter(|cells| !cells.is_empty())
+        .map(|mut cells| {
+            cells.sort_unstable();
+            let sum = cells
+                .iter()
+                .filter_map(|&cell| value(cell))
+                .map(u32::from)
+                .sum();
+            let cells = cells
+                .iter()
+                .map(|&cell| (cell / size, cell % size))
+                .collect();
+            Cage::new(sum, cells)
+        })
+        .collect();
+    cages.sort_by_key(|cage| cage.cells[0]);
+    cages
+}
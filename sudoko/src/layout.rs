@@ -0,0 +1,520 @@
+//! The houses of a grid: groups of cells that must each hold every value exactly once
+
+use crate::error::SudokuError;
+use crate::killer::{sum_range, Cage};
+use crate::sudoku::{full_mask, Unit, UnitKind};
+use crate::variant::Variant;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Which cells form the houses of a grid: its rows and columns, `size` boxes partitioning
+/// it, and any further houses a variant adds. Every house has `size` cells, so each value
+/// appears in it exactly once. Variants may also make neighbouring cells, such as those a
+/// knight's move apart, hold different values without sharing a house, and Killer cages
+/// make their cells add up to a sum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Layout {
+    size: usize,
+    /// Rows, then columns, then boxes, then extra houses in the order they were added
+    units: Vec<Unit>,
+    /// Cells of each unit, as row-major indices in ascending order
+    unit_cells: Vec<Vec<usize>>,
+    /// Units containing each cell, in unit order
+    cell_units: Vec<Vec<usize>>,
+    /// Cells sharing a unit with each cell or neighbouring it, not counting the cell itself,
+    /// in ascending order
+    peers: Vec<Vec<usize>>,
+    /// Box of each cell
+    boxes: Vec<usize>,
+    /// Rows and columns of each box, when the boxes are rectangles tiling the grid
+    box_shape: Option<(usize, usize)>,
+    /// Cells that must hold a different value from each cell without sharing a house with
+    /// it, or by sharing a cage with it, in ascending order
+    neighbours: Vec<Vec<usize>>,
+    /// Variant rules added to the grid, in the order they were added
+    variants: Vec<Variant>,
+    /// Killer cages, in the order they were added
+    cages: Vec<Cage>,
+    /// Cage of each cell, if any
+    cell_cages: Vec<Option<usize>>,
+}
+
+impl Layout {
+    /// Rows, columns and the usual boxes for `size`, as `box_shape_for` gives them: square
+    /// as in classic Sudoku, or rectangles such as the 2x3 boxes of a 6x6 grid
+    pub fn standard(size: usize) -> Result<Self, SudokuError> {
+        let (box_rows, box_cols) =
+            Self::box_shape_for(size).ok_or(SudokuError::InvalidSize(size))?;
+        Self::rectangular(box_rows, box_cols)
+    }
+
+    /// Rows, columns and boxes of `box_rows` by `box_cols` cells tiling a grid of
+    /// `box_rows * box_cols`, such as boxes 3 rows tall and 2 columns wide on a 6x6 grid
+    pub fn rectangular(box_rows: usize, box_cols: usize) -> Result<Self, SudokuError> {
+        let size = box_rows * box_cols;
+        if box_rows.min(box_cols) < 2 && size != 1 {
+            return Err(SudokuError::InvalidLayout(format!(
+                "{}x{} boxes are too narrow, boxes need at least two rows and two columns",
+                box_rows, box_cols
+            )));
+        }
+        let boxes = (0..size * size)
+            .map(|i| (i / size) / box_rows * (size / box_cols) + (i % size) / box_cols)
+            .collect();
+        let mut layout = Self::with_boxes(size, boxes)?;
+        layout.box_shape = Some((box_rows, box_cols));
+        Ok(layout)
+    }
+
+    /// The usual rows and columns of each box of a grid of `size`: square where `size` is
+    /// a square, otherwise the squarest rectangle, wider than tall, such as 2x3 for 6x6 and
+    /// 3x4 for 12x12. Sizes without a divisor other than 1 and themselves have none.
+    pub fn box_shape_for(size: usize) -> Option<(usize, usize)> {
+        if size == 1 {
+            return Some((1, 1));
+        }
+        (2..size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|rows| size / rows * rows == size)
+            .last()
+            .map(|rows| (rows, size / rows))
+    }
+
+    /// Rows, columns and the given boxes: the box of each cell in row-major order, numbered
+    /// from 0, with `size` cells in each
+    pub fn with_boxes(size: usize, boxes: Vec<usize>) -> Result<Self, SudokuError> {
+        if size == 0 || size > u32::BITS as usize - 1 {
+            return Err(SudokuError::InvalidSize(size));
+        }
+        if boxes.len() != size * size {
+            return Err(SudokuError::InvalidLayout(format!(
+                "{} cells have a box, expected {}",
+                boxes.len(),
+                size * size
+            )));
+        }
+        let mut box_cells = vec![Vec::new(); size];
+        for (cell, &b) in boxes.iter().enumerate() {
+            match box_cells.get_mut(b) {
+                Some(cells) => cells.push(cell),
+                None => {
+                    return Err(SudokuError::InvalidLayout(format!(
+                        "box {} is out of range",
+                        b + 1
+                    )))
+                }
+            }
+        }
+        if let Some(b) = box_cells.iter().position(|cells| cells.len() != size) {
+            return Err(SudokuError::InvalidLayout(format!(
+                "box {} has {} cells, expected {}",
+                b + 1,
+                box_cells[b].len(),
+                size
+            )));
+        }
+
+        let mut layout = Self {
+            size,
+            units: Vec::new(),
+            unit_cells: Vec::new(),
+            cell_units: vec![Vec::new(); size * size],
+            peers: vec![Vec::new(); size * size],
+            boxes,
+            box_shape: None,
+            neighbours: vec![Vec::new(); size * size],
+            variants: Vec::new(),
+            cages: Vec::new(),
+            cell_cages: vec![None; size * size],
+        };
+        for index in 0..size {
+            layout.push_unit(
+                Unit::new(UnitKind::Row, index),
+                (0..size).map(|col| index * size + col).collect(),
+            );
+        }
+        for index in 0..size {
+            layout.push_unit(
+                Unit::new(UnitKind::Column, index),
+                (0..size).map(|row| row * size + index).collect(),
+            );
+        }
+        for (index, cells) in box_cells.into_iter().enumerate() {
+            layout.push_unit(Unit::new(UnitKind::Box, index), cells);
+        }
+        Ok(layout)
+    }
+
+    /// Rows, columns and the usual boxes for `size`, with the rules of `variants`
+    pub fn with_variants(size: usize, variants: &[Variant]) -> Result<Self, SudokuError> {
+        let mut layout = Self::standard(size)?;
+        for &variant in variants {
+            layout.add_variant(variant)?;
+        }
+        Ok(layout)
+    }
+
+    /// Rows, columns and irregular regions, as in Jigsaw Sudoku. `map` has a character for
+    /// each cell in row-major order, the same one for every cell of a region; whitespace is
+    /// ignored. There must be `size` regions of `size` cells, each joined edge to edge.
+    pub fn from_region_map(map: &str, size: usize) -> Result<Self, SudokuError> {
+        if size == 0 || size > u32::BITS as usize - 1 {
+            return Err(SudokuError::InvalidSize(size));
+        }
+        let marks: Vec<char> = map.chars().filter(|ch| !ch.is_whitespace()).collect();
+        if marks.len() != size * size {
+            return Err(SudokuError::InvalidLayout(format!(
+                "region map has {} cells, expected {}",
+                marks.len(),
+                size * size
+            )));
+        }
+
+        // Regions are numbered in the order they first appear
+        let mut labels: Vec<char> = Vec::new();
+        let mut boxes = Vec::with_capacity(marks.len());
+        for &mark in &marks {
+            let region = match labels.iter().position(|&label| label == mark) {
+                Some(region) => region,
+                None => {
+                    labels.push(mark);
+                    labels.len() - 1
+                }
+            };
+            boxes.push(region);
+        }
+        if labels.len() != size {
+            return Err(SudokuError::InvalidLayout(format!(
+                "region map has {} regions, expected {}",
+                labels.len(),
+                size
+            )));
+        }
+        for (region, &label) in labels.iter().enumerate() {
+            let cells = boxes.iter().filter(|&&b| b == region).count();
+            if cells != size {
+                return Err(SudokuError::InvalidLayout(format!(
+                    "region '{}' has {} cells, expected {}",
+                    label, cells, size
+                )));
+            }
+            if !is_connected(&boxes, size, region) {
+                return Err(SudokuError::InvalidLayout(format!(
+                    "region '{}' is not connected",
+                    label
+                )));
+            }
+        }
+        Self::with_boxes(size, boxes)
+    }
+
+    /// Add a house of `size` cells that must hold every value once, such as a diagonal.
+    /// Rows, columns and boxes come with the layout, so `kind` must be another kind.
+    pub fn add_house(
+        &mut self,
+        kind: UnitKind,
+        cells: &[(usize, usize)],
+    ) -> Result<Unit, SudokuError> {
+        if matches!(kind, UnitKind::Row | UnitKind::Column | UnitKind::Box) {
+            return Err(SudokuError::InvalidLayout(format!(
+                "{:?} houses come with the layout",
+                kind
+            )));
+        }
+        if kind == UnitKind::Cage {
+            return Err(SudokuError::InvalidLayout(
+                "cages are added with add_cage".to_string(),
+            ));
+        }
+        let mut indices = Vec::with_capacity(cells.len());
+        for &(row, col) in cells {
+            if row >= self.size || col >= self.size {
+                return Err(SudokuError::InvalidPosition { row, col });
+            }
+            indices.push(row * self.size + col);
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.len() != self.size {
+            return Err(SudokuError::InvalidLayout(format!(
+                "a house needs {} different cells, got {}",
+                self.size,
+                indices.len()
+            )));
+        }
+
+        let index = self.units.iter().filter(|unit| unit.kind == kind).count();
+        let unit = Unit::new(kind, index);
+        self.push_unit(unit, indices);
+        Ok(unit)
+    }
+
+    /// Add the houses and neighbours of a variant rule; adding one again changes nothing
+    pub fn add_variant(&mut self, variant: Variant) -> Result<(), SudokuError> {
+        if self.variants.contains(&variant) {
+            return Ok(());
+        }
+        for (kind, cells) in variant.houses(self.size)? {
+            self.add_house(kind, &cells)?;
+        }
+        self.add_neighbours(variant.moves());
+        self.variants.push(variant);
+        Ok(())
+    }
+
+    /// Make every two cells a move in `moves` apart, either way, hold different values, as
+    /// in anti-knight Sudoku with the moves of a knight
+    pub fn add_neighbours(&mut self, moves: &[(isize, isize)]) {
+        let size = self.size as isize;
+        for cell in 0..self.size * self.size {
+            let (row, col) = (cell as isize / size, cell as isize % size);
+            let mut others = Vec::new();
+            for &(dr, dc) in moves {
+                for (r, c) in [(row + dr, col + dc), (row - dr, col - dc)] {
+                    if (0..size).contains(&r) && (0..size).contains(&c) && (r, c) != (row, col) {
+                        others.push((r * size + c) as usize);
+                    }
+                }
+            }
+            self.link(cell, &others);
+        }
+    }
+
+    /// Add a Killer cage: its cells must add up to its sum without repeating a value. A
+    /// cell can be in one cage at most.
+    pub fn add_cage(&mut self, mut cage: Cage) -> Result<(), SudokuError> {
+        let mut indices = Vec::with_capacity(cage.cells.len());
+        for &(row, col) in &cage.cells {
+            if row >= self.size || col >= self.size {
+                return Err(SudokuError::InvalidPosition { row, col });
+            }
+            let cell = row * self.size + col;
+            if indices.contains(&cell) || self.cell_cages[cell].is_some() {
+                return Err(SudokuError::InvalidCage(format!(
+                    "r{}c{} is in more than one cage",
+                    row + 1,
+                    col + 1
+                )));
+            }
+            indices.push(cell);
+        }
+        match sum_range(full_mask(self.size), indices.len()) {
+            Some((low, high)) if !indices.is_empty() && low <= cage.sum && cage.sum <= high => {}
+            _ => {
+                return Err(SudokuError::InvalidCage(format!(
+                    "{} different values cannot add up to {}",
+                    indices.len(),
+                    cage.sum
+                )))
+            }
+        }
+
+        indices.sort_unstable();
+        cage.cells.sort_unstable();
+        let id = self.cages.len();
+        for &cell in &indices {
+            self.cell_cages[cell] = Some(id);
+            let others: Vec<usize> = indices.iter().copied().filter(|&o| o != cell).collect();
+            self.link(cell, &others);
+        }
+        self.cages.push(cage);
+        Ok(())
+    }
+
+    /// Make `cell` hold a different value from each of `others`
+    fn link(&mut self, cell: usize, others: &[usize]) {
+        let neighbours = &mut self.neighbours[cell];
+        neighbours.extend(others.iter().copied());
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        self.peers[cell].extend(others.iter().copied());
+        self.peers[cell].sort_unstable();
+        self.peers[cell].dedup();
+    }
+
+    fn push_unit(&mut self, unit: Unit, cells: Vec<usize>) {
+        let id = self.units.len();
+        for &cell in &cells {
+            self.cell_units[cell].push(id);
+            self.peers[cell].extend(cells.iter().copied().filter(|&other| other != cell));
+            self.peers[cell].sort_unstable();
+            self.peers[cell].dedup();
+        }
+        self.units.push(unit);
+        self.unit_cells.push(cells);
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Every house: rows, then columns, then boxes, then extra houses
+    pub fn units(&self) -> &[Unit] {
+        &self.units
+    }
+
+    /// Cells of a unit in row-major order; for a cage, those of the cage with its index
+    pub fn unit_cells(&self, unit: Unit) -> Vec<(usize, usize)> {
+        if unit.kind == UnitKind::Cage {
+            return self.cages[unit.index].cells.clone();
+        }
+        self.cells_of(self.unit_id(unit))
+            .iter()
+            .map(|&cell| (cell / self.size, cell % self.size))
+            .collect()
+    }
+
+    /// Variant rules of the grid, in the order they were added
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    /// Index of the box containing a cell
+    pub fn box_index(&self, row: usize, col: usize) -> usize {
+        self.boxes[row * self.size + col]
+    }
+
+    /// Rows and columns of each box, if the boxes are rectangles tiling the grid in bands,
+    /// as `standard` and `rectangular` make them
+    pub fn box_shape(&self) -> Option<(usize, usize)> {
+        self.box_shape
+    }
+
+    /// Killer cages of the grid, in the order they were added
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// Index in `cages` of the cage containing a cell, if any
+    pub fn cage_index(&self, row: usize, col: usize) -> Option<usize> {
+        self.cell_cages[row * self.size + col]
+    }
+
+    /// Whether the grid has cages, as in Killer Sudoku
+    pub fn is_killer(&self) -> bool {
+        !self.cages.is_empty()
+    }
+
+    /// The cages as `Cage::parse_list` reads them, one per line
+    pub fn cage_definitions(&self) -> String {
+        self.cages
+            .iter()
+            .map(|cage| cage.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// The box of each cell as a region map, as `from_region_map` reads, one row per line
+    /// with boxes numbered from `1`, then `A` from the tenth
+    pub fn region_map(&self) -> String {
+        self.boxes
+            .chunks(self.size)
+            .map(|row| row.iter().map(|&b| region_label(b)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Whether the boxes are irregular, so that their borders do not run straight across
+    /// the grid
+    pub fn is_jigsaw(&self) -> bool {
+        let size = self.size;
+        // Cells either side of a line between rows or columns that are in different boxes
+        let crossings = |line: usize, between_rows: bool| {
+            (0..size)
+                .filter(|&i| {
+                    let (a, b) = if between_rows {
+                        ((line - 1) * size + i, line * size + i)
+                    } else {
+                        (i * size + line - 1, i * size + line)
+                    };
+                    self.boxes[a] != self.boxes[b]
+                })
+                .count()
+        };
+        (1..size).any(|line| {
+            [true, false].iter().any(|&between_rows| {
+                let crossings = crossings(line, between_rows);
+                crossings != 0 && crossings != size
+            })
+        })
+    }
+
+    /// Position of a unit in `units`
+    pub(crate) fn unit_id(&self, unit: Unit) -> usize {
+        match unit.kind {
+            UnitKind::Row => unit.index,
+            UnitKind::Column => self.size + unit.index,
+            UnitKind::Box => 2 * self.size + unit.index,
+            _ => self
+                .units
+                .iter()
+                .position(|&other| other == unit)
+                .unwrap_or_else(|| panic!("{} is not a house of this layout", unit)),
+        }
+    }
+
+    /// Row-major indices of the cells of the unit with id `id`
+    pub(crate) fn cells_of(&self, id: usize) -> &[usize] {
+        &self.unit_cells[id]
+    }
+
+    /// Ids of the units containing the cell with row-major index `cell`
+    pub(crate) fn units_of(&self, cell: usize) -> &[usize] {
+        &self.cell_units[cell]
+    }
+
+    /// Row-major indices of the cells that must differ from `cell` by a variant rule or by
+    /// sharing a cage with it
+    pub(crate) fn neighbours_of(&self, cell: usize) -> &[usize] {
+        &self.neighbours[cell]
+    }
+
+    /// Whether any cells must differ by a variant rule or a cage
+    pub(crate) fn has_neighbours(&self) -> bool {
+        self.neighbours.iter().any(|cells| !cells.is_empty())
+    }
+
+    /// Row-major indices of the cells sharing a unit with `cell` or neighbouring it
+    pub(crate) fn peers_of(&self, cell: usize) -> &[usize] {
+        &self.peers[cell]
+    }
+}
+
+/// Whether the cells of `region` are joined edge to edge
+fn is_connected(boxes: &[usize], size: usize, region: usize) -> bool {
+    let start = match boxes.iter().position(|&b| b == region) {
+        Some(start) => start,
+        None => return true,
+    };
+    let mut seen = vec![false; boxes.len()];
+    seen[start] = true;
+    let mut stack = vec![start];
+    let mut reached = 0;
+    while let Some(cell) = stack.pop() {
+        reached += 1;
+        let (row, col) = (cell / size, cell % size);
+        let neighbours = [
+            (row > 0).then(|| cell - size),
+            (row + 1 < size).then(|| cell + size),
+            (col > 0).then(|| cell - 1),
+            (col + 1 < size).then(|| cell + 1),
+        ];
+        for next in neighbours.into_iter().flatten() {
+            if boxes[next] == region && !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    reached == boxes.iter().filter(|&&b| b == region).count()
+}
+
+/// `1` to `9` for the first boxes, then letters
+fn region_label(b: usize) -> char {
+    if b < 9 {
+        (b'1' + b as u8) as char
+    } else {
+        (b'A' + (b - 9) as u8) as char
+    }
+}
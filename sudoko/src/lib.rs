@@ -1,190 +1,126 @@
+mod batch;
+mod chains;
+mod dlx;
+mod error;
+mod fish;
+mod generator;
+mod killer;
+mod layout;
 mod solver;
 mod strategies;
 mod sudoku;
+mod uniqueness;
 mod utils;
+mod variant;
+mod wings;
 
 #[cfg(feature = "wasm")]
 mod wasm;
 
-pub use solver::{Difficulty, SudokuSolver};
+pub use batch::{generate_batch, BatchResult, BatchSolver, GeneratedPuzzle};
+pub use chains::*;
+pub use error::SudokuError;
+pub use fish::*;
+pub use generator::{GenerationOptions, Symmetry};
+pub use killer::{Cage, CageCombinations, InniesAndOuties};
+pub use layout::Layout;
+pub use solver::{
+    Difficulty, Grade, PuzzleAnalysis, SolverBackend, SolverStats, SudokuSolver, Uniqueness,
+};
 pub use strategies::*;
-pub use sudoku::{Cell, Sudoku};
+pub use sudoku::{
+    mask_values, value_bit, CageError, Cell, Clash, Conflict, Sudoku, Unit, UnitKind,
+    ValidationReport,
+};
+pub use uniqueness::*;
+pub use variant::Variant;
+pub use wings::*;
 
 #[cfg(feature = "wasm")]
 pub use wasm::*;
 
-use std::{fs, process};
-
-pub fn solve_puzzle(puzzle_str: &str, size_str: &str) {
-    let size = match size_str.parse::<usize>() {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("Invalid size: {}", size_str);
-            process::exit(1);
-        }
-    };
-
-    let puzzle = match Sudoku::from_string(puzzle_str, size) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Error parsing puzzle: {}", e);
-            process::exit(1);
-        }
-    };
-
-    println!("Original puzzle:");
-    println!("{}", puzzle);
-
-    let mut solver = SudokuSolver::new();
-
-    match solver.solve_with_stats(puzzle) {
-        Ok((solution, stats)) => {
-            println!("Solution found!");
-            println!("{}", solution);
-            println!("\nSolver Statistics:");
-            println!("Iterations: {}", stats.iterations);
-            println!("Cells filled: {}", stats.cells_filled);
-            println!("Backtrack steps: {}", stats.backtrack_steps);
-            println!("Strategies used:");
-            for (strategy, count) in stats.strategies_used {
-                println!("  {}: {}", strategy, count);
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to solve puzzle: {}", e);
-            process::exit(1);
-        }
-    }
+use std::fs;
+use std::path::Path;
+
+/// A value that can be placed next, found by the logical strategies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+    /// The next deduction on the way to the placement, with the technique and, for chains,
+    /// an explanation such as "if r3c5 is not 7 then..."
+    pub step: Step,
 }
 
-pub fn solve_from_file(file_path: &str, size_str: &str) {
-    let puzzle_str = match fs::read_to_string(file_path) {
-        Ok(content) => content.trim().to_string(),
-        Err(e) => {
-            eprintln!("Error reading file: {}", e);
-            process::exit(1);
-        }
-    };
-
-    solve_puzzle(&puzzle_str, size_str);
+/// Solve a puzzle given as text, with the statistics of the solve
+pub fn solve_puzzle(puzzle_str: &str, size: usize) -> Result<(Sudoku, SolverStats), SudokuError> {
+    let puzzle = Sudoku::from_string(puzzle_str, size)?;
+    SudokuSolver::new().solve_with_stats(puzzle)
 }
 
-pub fn generate_puzzle(size_str: &str, difficulty_str: &str) {
-    let size = match size_str.parse::<usize>() {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("Invalid size: {}", size_str);
-            process::exit(1);
-        }
-    };
-
-    let difficulty = match difficulty_str.to_lowercase().as_str() {
-        "easy" => Difficulty::Easy,
-        "medium" => Difficulty::Medium,
-        "hard" => Difficulty::Hard,
-        "expert" => Difficulty::Expert,
-        _ => {
-            eprintln!(
-                "Invalid difficulty: {}. Use easy, medium, hard, or expert",
-                difficulty_str
-            );
-            process::exit(1);
-        }
-    };
-
-    let mut solver = SudokuSolver::new();
+/// Solve a puzzle read from a text file
+pub fn solve_from_file(
+    file_path: impl AsRef<Path>,
+    size: usize,
+) -> Result<(Sudoku, SolverStats), SudokuError> {
+    let puzzle_str = fs::read_to_string(file_path)?;
+    solve_puzzle(puzzle_str.trim(), size)
+}
 
-    match solver.generate_puzzle(size, difficulty) {
-        Ok(puzzle) => {
-            println!("Generated {} puzzle ({}x{}):", difficulty_str, size, size);
-            println!("{}", puzzle);
-        }
-        Err(e) => {
-            eprintln!("Failed to generate puzzle: {}", e);
-            process::exit(1);
-        }
+pub fn generate_puzzle(size: usize, difficulty: Difficulty) -> Result<Sudoku, SudokuError> {
+    if Layout::box_shape_for(size).is_none() {
+        return Err(SudokuError::InvalidSize(size));
     }
+    SudokuSolver::new().generate_puzzle(size, difficulty)
 }
 
-pub fn validate_puzzle(puzzle_str: &str, size_str: &str) {
-    let size = match size_str.parse::<usize>() {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("Invalid size: {}", size_str);
-            process::exit(1);
-        }
-    };
-
-    let puzzle = match Sudoku::from_string(puzzle_str, size) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Error parsing puzzle: {}", e);
-            process::exit(1);
-        }
-    };
-
-    println!("Puzzle:");
-    println!("{}", puzzle);
-
-    if puzzle.is_valid() {
-        println!("✓ Puzzle is valid!");
-
-        if puzzle.is_complete() {
-            println!("✓ Puzzle is complete and solved!");
-        } else {
-            println!("! Puzzle is valid but not yet complete.");
-        }
-    } else {
-        println!("✗ Puzzle is invalid!");
-
-        if !puzzle.is_valid_rows() {
-            println!("  - Invalid rows detected");
-        }
-        if !puzzle.is_valid_cols() {
-            println!("  - Invalid columns detected");
-        }
-        if !puzzle.is_valid_boxes() {
-            println!("  - Invalid boxes detected");
-        }
+/// Generate a puzzle reproducibly: the same size, difficulty and seed give the same puzzle
+pub fn generate_puzzle_seeded(
+    size: usize,
+    difficulty: Difficulty,
+    seed: u64,
+) -> Result<Sudoku, SudokuError> {
+    if Layout::box_shape_for(size).is_none() {
+        return Err(SudokuError::InvalidSize(size));
     }
+    SudokuSolver::new()
+        .generate_with_options(size, &GenerationOptions::new(difficulty).with_seed(seed))
 }
 
-pub fn get_hint(puzzle_str: &str, size_str: &str) {
-    let size = match size_str.parse::<usize>() {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("Invalid size: {}", size_str);
-            process::exit(1);
-        }
-    };
-
-    let mut puzzle = match Sudoku::from_string(puzzle_str, size) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Error parsing puzzle: {}", e);
-            process::exit(1);
-        }
-    };
+pub fn validate_puzzle(puzzle_str: &str, size: usize) -> Result<ValidationReport, SudokuError> {
+    Ok(Sudoku::from_string(puzzle_str, size)?.validate_detailed())
+}
 
-    println!("Current puzzle:");
-    println!("{}", puzzle);
+/// Rate a puzzle by the hardest technique needed to solve it by logic
+pub fn rate_puzzle(puzzle_str: &str, size: usize) -> Result<Grade, SudokuError> {
+    let puzzle = Sudoku::from_string(puzzle_str, size)?;
+    SudokuSolver::new().grade(&puzzle)
+}
 
+/// Check whether a puzzle has a unique solution and whether any of its givens are redundant
+pub fn check_puzzle(puzzle_str: &str, size: usize) -> Result<PuzzleAnalysis, SudokuError> {
+    let puzzle = Sudoku::from_string(puzzle_str, size)?;
     let mut solver = SudokuSolver::new();
+    solver.set_backend(SolverBackend::DancingLinks);
+    Ok(solver.analyze(&puzzle))
+}
 
-    match solver.get_hint(&mut puzzle) {
-        Some((row, col, value)) => {
-            println!(
-                "Hint: Place {} at position ({}, {})",
-                value,
-                row + 1,
-                col + 1
-            );
-            puzzle.set(row, col, value).unwrap();
-            println!("\nPuzzle with hint applied:");
-            println!("{}", puzzle);
-        }
-        None => {
-            println!("No obvious hint available. You might need to use more advanced techniques.");
-        }
-    }
+/// The next value the logical strategies can place, if any
+pub fn get_hint(puzzle_str: &str, size: usize) -> Result<Option<Hint>, SudokuError> {
+    let puzzle = Sudoku::from_string(puzzle_str, size)?;
+    let solver = SudokuSolver::new();
+    let hint = solver
+        .hint_step(&puzzle)
+        .zip(solver.next_step(&puzzle))
+        .map(|(placing, step)| {
+            let placement = placing.placements[0];
+            Hint {
+                row: placement.row,
+                col: placement.col,
+                value: placement.value,
+                step,
+            }
+        });
+    Ok(hint)
 }
@@ -1,10 +1,16 @@
 use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::process;
-use sudoko::generate_puzzle;
-use sudoko::get_hint;
-use sudoko::solve_from_file;
-use sudoko::solve_puzzle;
-use sudoko::validate_puzzle;
+use std::str::FromStr;
+use std::time::Instant;
+use sudoko::{
+    BatchResult, BatchSolver, Cage, Difficulty, GenerationOptions, Layout, SolverBackend,
+    SolverStats, Sudoku, SudokuError, SudokuSolver, Uniqueness, Variant,
+};
+
+/// Largest cage of the Killer puzzles `generate --killer` makes
+const KILLER_CAGE_SIZE: usize = 5;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -16,40 +22,93 @@ fn main() {
 
     match args[1].as_str() {
         "solve" => {
-            if args.len() < 4 {
-                eprintln!("Usage: {} solve <puzzle_string> <size>", args[0]);
-                process::exit(1);
-            }
-            solve_puzzle(&args[2], &args[3]);
+            let (puzzle, size, rules) = puzzle_args(&args, "<puzzle_string>");
+            solve_puzzle(puzzle, size, &rules);
         }
         "solve-file" => {
-            if args.len() < 4 {
-                eprintln!("Usage: {} solve-file <file_path> <size>", args[0]);
-                process::exit(1);
-            }
-            solve_from_file(&args[2], &args[3]);
+            let (puzzle, size, rules) = puzzle_args(&args, "<file_path>");
+            solve_from_file(puzzle, size, &rules);
         }
         "generate" => {
-            if args.len() < 3 {
-                eprintln!("Usage: {} generate <size> [difficulty]", args[0]);
+            let mut positional = Vec::new();
+            let mut seed = None;
+            let mut count = 1;
+            let mut jobs = 1;
+            let mut variants = Vec::new();
+            let mut killer = false;
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--seed" => seed = Some(option_value(arg, rest.next())),
+                    "--killer" => killer = true,
+                    "--variant" => variants.extend(parse_variants(option_value(arg, rest.next()))),
+                    "--count" => count = parse_count(arg, option_value(arg, rest.next())),
+                    "--jobs" => jobs = parse_count(arg, option_value(arg, rest.next())),
+                    _ => positional.push(arg.as_str()),
+                }
+            }
+            if positional.is_empty() {
+                eprintln!(
+                    "Usage: {} generate <size> [difficulty] [--seed <number>] [--count <n>] [--jobs <n>] [--variant <rules>] [--killer]",
+                    args[0]
+                );
                 process::exit(1);
             }
-            let difficulty = args.get(3).map(|s| s.as_str()).unwrap_or("medium");
-            generate_puzzle(&args[2], difficulty);
+            let difficulty = positional.get(1).copied().unwrap_or("medium");
+            let mut options = variants.iter().fold(
+                GenerationOptions::new(Difficulty::Medium),
+                |options, &variant| options.with_variant(variant),
+            );
+            if killer {
+                options = options.with_killer_cages(KILLER_CAGE_SIZE);
+            }
+            generate_puzzles(positional[0], difficulty, seed, count, jobs, options);
         }
         "validate" => {
-            if args.len() < 4 {
-                eprintln!("Usage: {} validate <puzzle_string> <size>", args[0]);
-                process::exit(1);
+            let (puzzle, size, rules) = puzzle_args(&args, "<puzzle_string>");
+            validate_puzzle(puzzle, size, &rules);
+        }
+        "rate" => {
+            let (puzzle, size, rules) = puzzle_args(&args, "<puzzle_string>");
+            rate_puzzle(puzzle, size, &rules);
+        }
+        "check" => {
+            let (puzzle, size, rules) = puzzle_args(&args, "<puzzle_string>");
+            check_puzzle(puzzle, size, &rules);
+        }
+        "batch" => {
+            let mut path = None;
+            let mut size = None;
+            let mut format = BatchFormat::Text;
+            let mut jobs = 1;
+            let mut variants = Vec::new();
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--size" => size = Some(parse_size(option_value(arg, rest.next()))),
+                    "--format" => {
+                        format = option_value(arg, rest.next()).parse().unwrap_or_else(|e| {
+                            eprintln!("{}", e);
+                            process::exit(1);
+                        })
+                    }
+                    "--jobs" => jobs = parse_count(arg, option_value(arg, rest.next())),
+                    "--variant" => variants.extend(parse_variants(option_value(arg, rest.next()))),
+                    _ if path.is_none() => path = Some(arg.as_str()),
+                    _ => {
+                        eprintln!(
+                            "Usage: {} batch [file] [--size <size>] [--format text|csv|jsonl] [--jobs <n>] [--variant <rules>]",
+                            args[0]
+                        );
+                        process::exit(1);
+                    }
+                }
             }
-            validate_puzzle(&args[2], &args[3]);
+            solve_batch(path, size, format, jobs, &variants);
         }
         "hint" => {
-            if args.len() < 4 {
-                eprintln!("Usage: {} hint <puzzle_string> <size>", args[0]);
-                process::exit(1);
-            }
-            get_hint(&args[2], &args[3]);
+            let (puzzle, size, rules) = puzzle_args(&args, "<puzzle_string>");
+            get_hint(puzzle, size, &rules);
         }
         _ => {
             print_usage();
@@ -58,20 +117,655 @@ fn main() {
     }
 }
 
+/// A grid size given on the command line, with the shape of its boxes if one was given
+#[derive(Clone, Copy)]
+struct GridSize {
+    size: usize,
+    /// Rows and columns of each box; `None` for the usual shape for the size
+    box_shape: Option<(usize, usize)>,
+}
+
+/// A size such as `9`, or a box shape such as `3x2` for a 6x6 grid of boxes 3 rows tall and
+/// 2 columns wide
+fn parse_size(size_str: &str) -> GridSize {
+    let box_shape = size_str
+        .split_once('x')
+        .and_then(|(rows, cols)| Some((rows.parse::<usize>().ok()?, cols.parse::<usize>().ok()?)));
+    match (size_str.parse::<usize>(), box_shape) {
+        (Ok(size), _) => GridSize {
+            size,
+            box_shape: None,
+        },
+        (_, Some((rows, cols))) if rows.checked_mul(cols).is_some() => GridSize {
+            size: rows * cols,
+            box_shape: Some((rows, cols)),
+        },
+        _ => {
+            eprintln!("Invalid size: {}", size_str);
+            process::exit(1);
+        }
+    }
+}
+
+/// The value following a `--name` option
+fn option_value<'a>(name: &str, value: Option<&'a String>) -> &'a str {
+    match value {
+        Some(value) => value,
+        None => {
+            eprintln!("{} needs a value", name);
+            process::exit(1);
+        }
+    }
+}
+
+/// A count of at least one, such as puzzles or jobs
+fn parse_count(name: &str, value: &str) -> usize {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => {
+            eprintln!("Invalid value for {}: {}", name, value);
+            process::exit(1);
+        }
+    }
+}
+
+/// The rules a puzzle is played by beyond rows, columns and boxes
+#[derive(Default)]
+struct Rules<'a> {
+    /// Region map of a Jigsaw puzzle
+    regions: Option<&'a str>,
+    variants: Vec<Variant>,
+    /// Cages of a Killer puzzle
+    cages: Vec<Cage>,
+}
+
+/// The puzzle and size given to a command, and the rules given with `--regions`,
+/// `--variant` and `--cages`
+fn puzzle_args<'a>(args: &'a [String], puzzle: &str) -> (&'a str, &'a str, Rules<'a>) {
+    let mut positional = Vec::new();
+    let mut rules = Rules::default();
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--regions" => rules.regions = Some(option_value(arg, rest.next())),
+            "--variant" => rules
+                .variants
+                .extend(parse_variants(option_value(arg, rest.next()))),
+            "--cages" => rules
+                .cages
+                .extend(read_cages(option_value(arg, rest.next()))),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+    if positional.len() != 2 {
+        eprintln!(
+            "Usage: {} {} {} <size> [--regions <map>] [--variant <rules>] [--cages <file>]",
+            args[0], args[1], puzzle
+        );
+        process::exit(1);
+    }
+    (positional[0], positional[1], rules)
+}
+
+/// Variant rules separated by commas, such as `diagonal,anti-knight`
+fn parse_variants(value: &str) -> Vec<Variant> {
+    Variant::parse_list(value).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+/// Killer cages read from a file with one cage per line, such as `15: r1c1 r1c2 r2c1`
+fn read_cages(path: &str) -> Vec<Cage> {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Error reading file: {}", e);
+        process::exit(1);
+    });
+    Cage::parse_list(&text).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+fn parse_puzzle(puzzle_str: &str, size: GridSize, rules: &Rules) -> Sudoku {
+    let layout = match (rules.regions, size.box_shape) {
+        (Some(regions), _) => Layout::from_region_map(regions, size.size),
+        (None, Some((box_rows, box_cols))) => Layout::rectangular(box_rows, box_cols),
+        (None, None) => Layout::standard(size.size),
+    };
+    let puzzle = layout.and_then(|mut layout| {
+        for &variant in &rules.variants {
+            layout.add_variant(variant)?;
+        }
+        for cage in &rules.cages {
+            layout.add_cage(cage.clone())?;
+        }
+        Sudoku::from_string_with_layout(puzzle_str, layout)
+    });
+    match puzzle {
+        Ok(puzzle) => puzzle,
+        Err(e) => {
+            eprintln!("Error parsing puzzle: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_solution(result: Result<(Sudoku, SolverStats), SudokuError>) {
+    match result {
+        Ok((solution, stats)) => {
+            println!("Solution found!");
+            println!("{}", solution);
+            println!("\nSolver Statistics:");
+            println!("Iterations: {}", stats.iterations);
+            println!("Cells filled: {}", stats.cells_filled);
+            println!("Backtrack steps: {}", stats.backtrack_steps);
+            println!("Strategies used:");
+            for (strategy, count) in stats.strategies_used {
+                println!("  {}: {}", strategy, count);
+            }
+            if !stats.cage_techniques.is_empty() {
+                println!("Cage techniques: {}", stats.cage_techniques.join(", "));
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to solve puzzle: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn solve_puzzle(puzzle_str: &str, size_str: &str, rules: &Rules) {
+    let size = parse_size(size_str);
+    let puzzle = parse_puzzle(puzzle_str, size, rules);
+
+    println!("Original puzzle:");
+    println!("{}", puzzle);
+
+    print_solution(SudokuSolver::new().solve_with_stats(puzzle));
+}
+
+fn solve_from_file(file_path: &str, size_str: &str, rules: &Rules) {
+    let puzzle_str = match fs::read_to_string(file_path) {
+        Ok(content) => content.trim().to_string(),
+        Err(e) => {
+            eprintln!("Error reading file: {}", e);
+            process::exit(1);
+        }
+    };
+
+    solve_puzzle(&puzzle_str, size_str, rules);
+}
+
+/// How `batch` writes its results
+#[derive(Clone, Copy)]
+enum BatchFormat {
+    Text,
+    Csv,
+    JsonLines,
+}
+
+impl FromStr for BatchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(BatchFormat::Text),
+            "csv" => Ok(BatchFormat::Csv),
+            "jsonl" | "json" => Ok(BatchFormat::JsonLines),
+            _ => Err(format!("Invalid format: {}. Use text, csv or jsonl", s)),
+        }
+    }
+}
+
+fn solve_batch(
+    path: Option<&str>,
+    size: Option<GridSize>,
+    format: BatchFormat,
+    jobs: usize,
+    variants: &[Variant],
+) {
+    // Read standard input when no file, or `-`, is given
+    let reader: Box<dyn BufRead> = match path {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => match fs::File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => {
+                eprintln!("Error reading file: {}", e);
+                process::exit(1);
+            }
+        },
+    };
+    let mut batch = variants.iter().fold(
+        BatchSolver::new(reader).with_jobs(jobs),
+        |batch, &variant| batch.with_variant(variant),
+    );
+    match size {
+        Some(GridSize {
+            box_shape: Some((box_rows, box_cols)),
+            ..
+        }) => batch = batch.with_box_shape(box_rows, box_cols),
+        Some(size) => batch = batch.with_size(size.size),
+        None => {}
+    }
+
+    let start = Instant::now();
+    let mut counts = (0, 0);
+    // A closed pipe, as with `| head`, just ends the output
+    if let Err(e) = write_batch(batch, format, &mut counts) {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("Error writing results: {}", e);
+            process::exit(1);
+        }
+    }
+
+    let (solved, total) = counts;
+    eprintln!(
+        "Solved {} of {} puzzles in {:.2?}",
+        solved,
+        total,
+        start.elapsed()
+    );
+}
+
+/// Write each result to standard output as it comes, counting (solved, total) puzzles
+fn write_batch(
+    results: impl Iterator<Item = BatchResult>,
+    format: BatchFormat,
+    counts: &mut (usize, usize),
+) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    if let BatchFormat::Csv = format {
+        writeln!(
+            out,
+            "line,puzzle,solution,error,time_ms,backtracks,strategies"
+        )?;
+    }
+    for result in results {
+        counts.1 += 1;
+        if result.is_solved() {
+            counts.0 += 1;
+        }
+        writeln!(out, "{}", format_batch_result(&result, format))?;
+    }
+    out.flush()
+}
+
+fn format_batch_result(result: &BatchResult, format: BatchFormat) -> String {
+    // Sorted, so the same puzzles always give the same output
+    let mut strategies: Vec<(&String, &usize)> = result.stats.strategies_used.iter().collect();
+    strategies.sort();
+    let time_ms = result.elapsed.as_secs_f64() * 1000.0;
+    let solution = result.solution.as_ref().map(Sudoku::to_line);
+
+    match format {
+        BatchFormat::Text => match solution {
+            Ok(solution) => {
+                let strategies: String = strategies
+                    .iter()
+                    .map(|(name, count)| format!("; {} {}", name, count))
+                    .collect();
+                format!(
+                    "{}: {} ({:.3} ms, {} backtracks{})",
+                    result.line, solution, time_ms, result.stats.backtrack_steps, strategies
+                )
+            }
+            Err(e) => format!("{}: {} failed: {}", result.line, result.puzzle, e),
+        },
+        BatchFormat::Csv => {
+            let strategies: Vec<String> = strategies
+                .iter()
+                .map(|(name, count)| format!("{}:{}", name, count))
+                .collect();
+            let (solution, error) = match solution {
+                Ok(solution) => (solution, String::new()),
+                Err(e) => (String::new(), e.to_string()),
+            };
+            [
+                result.line.to_string(),
+                result.puzzle.clone(),
+                solution,
+                error,
+                format!("{:.3}", time_ms),
+                result.stats.backtrack_steps.to_string(),
+                strategies.join(";"),
+            ]
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",")
+        }
+        BatchFormat::JsonLines => {
+            let strategies: Vec<String> = strategies
+                .iter()
+                .map(|(name, count)| format!("{}:{}", json_string(name), count))
+                .collect();
+            let (solution, error) = match solution {
+                Ok(solution) => (json_string(&solution), "null".to_string()),
+                Err(e) => ("null".to_string(), json_string(&e.to_string())),
+            };
+            format!(
+                "{{\"line\":{},\"puzzle\":{},\"solution\":{},\"error\":{},\"time_ms\":{:.3},\"backtracks\":{},\"strategies\":{{{}}}}}",
+                result.line,
+                json_string(&result.puzzle),
+                solution,
+                error,
+                time_ms,
+                result.stats.backtrack_steps,
+                strategies.join(",")
+            )
+        }
+    }
+}
+
+/// Quote a CSV field if it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A JSON string literal
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn generate_puzzles(
+    size_str: &str,
+    difficulty_str: &str,
+    seed_str: Option<&str>,
+    count: usize,
+    jobs: usize,
+    options: GenerationOptions,
+) {
+    let size = parse_size(size_str);
+    let difficulty: Difficulty = match difficulty_str.parse() {
+        Ok(difficulty) => difficulty,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    // Always generate from a known seed so the puzzles can be made again
+    let seed = match seed_str.map(str::parse::<u64>) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            eprintln!("Invalid seed: {}", seed_str.unwrap_or_default());
+            process::exit(1);
+        }
+        None => rand::random(),
+    };
+
+    let mut options = GenerationOptions {
+        difficulty,
+        ..options
+    }
+    .with_seed(seed);
+    if let Some((box_rows, box_cols)) = size.box_shape {
+        options = options.with_box_shape(box_rows, box_cols);
+    }
+    let generated = match sudoko::generate_batch(size.size, &options, count, jobs) {
+        Ok(generated) => generated,
+        Err(e) => {
+            eprintln!("Failed to generate puzzle: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let solver = SudokuSolver::new();
+    let mut failed = false;
+    for (i, generated) in generated.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        match &generated.puzzle {
+            Ok(puzzle) => {
+                // Boxes are only worth naming when they are not square
+                let boxes = if puzzle.box_rows == puzzle.box_cols {
+                    String::new()
+                } else {
+                    format!(", {}x{} boxes", puzzle.box_rows, puzzle.box_cols)
+                };
+                // Generation settles for the closest easier grade when it misses the target
+                let achieved = solver
+                    .grade_without(puzzle, &options.forbidden_techniques)
+                    .map_or(difficulty, |grade| grade.difficulty);
+                println!(
+                    "Generated {:?} puzzle ({}x{}{}):",
+                    achieved, size.size, size.size, boxes
+                );
+                if achieved != difficulty {
+                    println!(
+                        "No {:?} puzzle was found, so this is the closest easier one",
+                        difficulty
+                    );
+                }
+                println!("{}", puzzle);
+                if !options.variants.is_empty() {
+                    let names: Vec<&str> = options
+                        .variants
+                        .iter()
+                        .map(|variant| variant.name())
+                        .collect();
+                    println!("Variants: {}", names.join(", "));
+                }
+                if puzzle.layout().is_killer() {
+                    println!("Cages:");
+                    println!("{}", puzzle.layout().cage_definitions());
+                }
+                println!("Clues: {}", puzzle.clue_count());
+                println!("Seed: {}", generated.seed);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to generate puzzle with seed {}: {}",
+                    generated.seed, e
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn validate_puzzle(puzzle_str: &str, size_str: &str, rules: &Rules) {
+    let size = parse_size(size_str);
+    let puzzle = parse_puzzle(puzzle_str, size, rules);
+    let report = puzzle.validate_detailed();
+
+    println!("Puzzle:");
+    println!("{}", puzzle);
+
+    if report.is_valid() {
+        println!("✓ Puzzle is valid!");
+
+        if report.complete {
+            println!("✓ Puzzle is complete and solved!");
+        } else {
+            println!("! Puzzle is valid but not yet complete.");
+        }
+    } else {
+        println!("✗ Puzzle is invalid!");
+
+        for conflict in &report.conflicts {
+            println!("  - {}", conflict);
+        }
+        for clash in &report.clashes {
+            println!("  - {}", clash);
+        }
+        for error in &report.cage_errors {
+            println!("  - {}", error);
+        }
+    }
+
+    for &(row, col) in &report.dead_cells {
+        println!("✗ Cell r{}c{} has no candidates left", row + 1, col + 1);
+    }
+}
+
+fn rate_puzzle(puzzle_str: &str, size_str: &str, rules: &Rules) {
+    let size = parse_size(size_str);
+    let puzzle = parse_puzzle(puzzle_str, size, rules);
+    let grade = match SudokuSolver::new().grade(&puzzle) {
+        Ok(grade) => grade,
+        Err(e) => {
+            eprintln!("Error rating puzzle: {}", e);
+            process::exit(1);
+        }
+    };
+
+    println!("Puzzle:");
+    println!("{}", puzzle);
+
+    println!("Rating: {:.1} ({:?})", grade.rating, grade.difficulty);
+    if let Some(technique) = &grade.hardest_technique {
+        println!("Hardest technique: {}", technique);
+    }
+    if !grade.techniques.is_empty() {
+        println!("Techniques: {}", grade.techniques.join(", "));
+    }
+    println!("Steps: {}", grade.steps);
+    if !grade.solved {
+        println!("! The logical strategies cannot finish this puzzle on their own");
+    }
+}
+
+fn format_cells(cells: &[(usize, usize)]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|&(row, col)| format!("r{}c{}", row + 1, col + 1))
+        .collect();
+    cells.join(", ")
+}
+
+fn check_puzzle(puzzle_str: &str, size_str: &str, rules: &Rules) {
+    let size = parse_size(size_str);
+    let puzzle = parse_puzzle(puzzle_str, size, rules);
+    let mut solver = SudokuSolver::new();
+    solver.set_backend(SolverBackend::DancingLinks);
+    let analysis = solver.analyze(&puzzle);
+
+    println!("Puzzle:");
+    println!("{}", puzzle);
+
+    match &analysis.uniqueness {
+        Uniqueness::NoSolution => println!("✗ Puzzle has no solution"),
+        Uniqueness::Unique(solution) => {
+            println!("✓ Puzzle has a unique solution:");
+            println!("{}", solution);
+
+            if analysis.is_minimal() {
+                println!("✓ Puzzle is minimal: every given is needed");
+            } else {
+                println!(
+                    "! Puzzle is not minimal: {} givens can each be removed: {}",
+                    analysis.redundant_givens.len(),
+                    format_cells(&analysis.redundant_givens)
+                );
+            }
+        }
+        Uniqueness::Multiple(first, second) => {
+            println!("✗ Puzzle has multiple solutions, for example:");
+            println!("{}", first);
+            println!("{}", second);
+            println!(
+                "Solutions differ at {}",
+                format_cells(&analysis.ambiguous_cells())
+            );
+        }
+    }
+}
+
+fn get_hint(puzzle_str: &str, size_str: &str, rules: &Rules) {
+    let size = parse_size(size_str);
+    let mut puzzle = parse_puzzle(puzzle_str, size, rules);
+
+    println!("Current puzzle:");
+    println!("{}", puzzle);
+
+    // The next step explains itself; the placement it leads to is applied
+    let solver = SudokuSolver::new();
+    match (solver.next_step(&puzzle), solver.hint_step(&puzzle)) {
+        (Some(step), Some(placing)) => {
+            println!("Hint: {}", step);
+            if let Some(explanation) = &step.explanation {
+                println!("Why: {}", explanation);
+            }
+            if placing != step {
+                println!("Which leads to: {}", placing);
+            }
+            let placement = placing.placements[0];
+            puzzle
+                .set(placement.row, placement.col, placement.value)
+                .unwrap();
+            println!("\nPuzzle with hint applied:");
+            println!("{}", puzzle);
+        }
+        _ => {
+            println!("No obvious hint available. You might need to use more advanced techniques.");
+        }
+    }
+}
+
 fn print_usage() {
     println!("Sudoku Solver Library");
     println!("Usage:");
     println!("  solve <puzzle_string> <size>     - Solve a Sudoku puzzle");
     println!("  solve-file <file_path> <size>    - Solve a Sudoku from file");
-    println!("  generate <size> [difficulty]     - Generate a new puzzle");
+    println!(
+        "  batch [file] [--size <size>] [--format text|csv|jsonl] [--jobs <n>] [--variant <rules>]"
+    );
+    println!("                                   - Solve one puzzle per line from a file or stdin");
+    println!("  generate <size> [difficulty] [--seed <number>] [--count <n>] [--jobs <n>] [--variant <rules>]");
+    println!("                                   - Generate new puzzles, reproducibly with a seed");
     println!("  validate <puzzle_string> <size>  - Validate a puzzle");
+    println!("  rate <puzzle_string> <size>      - Rate a puzzle by the hardest technique needed");
+    println!(
+        "  check <puzzle_string> <size>     - Check for a unique solution and redundant givens"
+    );
     println!("  hint <puzzle_string> <size>      - Get a hint for the next move");
     println!();
-    println!("Sizes supported: 4 (2x2), 9 (3x3), 16 (4x4), 25 (5x5)");
+    println!("Puzzle commands take --regions <map> for Jigsaw puzzles: a character per cell,");
+    println!("the same for each cell of a region, such as 111222333111222333...");
+    println!("Puzzle commands, batch and generate take --variant <rules> for extra rules,");
+    println!("separated by commas: diagonal (X-Sudoku), windoku, anti-knight, anti-king");
+    println!("Puzzle commands take --cages <file> for Killer puzzles, one cage per line such");
+    println!("as 15: r1c1 r1c2 r2c1, and generate takes --killer to make them");
+    println!();
+    println!("Sizes supported: 4 (2x2 boxes), 6 (2x3), 8 (2x4), 9 (3x3), 10 (2x5), 12 (3x4),");
+    println!("16 (4x4), 25 (5x5) and others with boxes, or a box shape such as 3x2 for a 6x6");
+    println!("grid of boxes 3 rows tall and 2 columns wide");
     println!("Difficulties: easy, medium, hard, expert");
     println!();
     println!("Examples:");
     println!("  solve \"530070000600195000098000060800060003400803001700020006060000280000419005000080079\" 9");
+    println!("  batch puzzles.sdm --format csv --jobs 4 > solutions.csv");
     println!("  generate 9 hard");
+    println!("  generate 9 hard --seed 20240101");
+    println!("  generate 16 easy --count 8 --jobs 4");
+    println!("  generate 6 easy");
+    println!("  generate 3x2 medium");
+    println!("  generate 9 hard --variant diagonal,anti-knight");
+    println!("  generate 9 medium --killer");
+    println!("  solve \"000000000000000000000000000000000000000000000000000000000000000000000000000000000\" 9 --cages cages.txt");
+    println!("  solve \"200000509100000000000240060430100200080000000600009800008070000000600000000000003\" 9 --regions \"111222333114222333114255333114255566444256666444555776888999776889999776888899777\"");
     println!("  validate \"123456789456789123789123456234567891567891234891234567345678912678912345912345678\" 9");
 }
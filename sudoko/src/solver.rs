@@ -1,15 +1,28 @@
-use crate::strategies::{get_all_strategies, SolvingStrategy};
-use crate::sudoku::{Cell, Sudoku};
+use crate::dlx::DancingLinks;
+use crate::error::SudokuError;
+use crate::generator::GenerationOptions;
+use crate::killer::random_cages;
+use crate::strategies::{get_all_strategies, SolvingStrategy, Step};
+use crate::sudoku::{full_mask, mask_values, value_bit, Sudoku};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{thread_rng, Rng};
+use rand::{thread_rng, Rng, SeedableRng};
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct SolverStats {
     pub strategies_used: HashMap<String, usize>,
     pub cells_filled: usize,
+    pub candidates_eliminated: usize,
     pub iterations: usize,
     pub backtrack_steps: usize,
+    /// Every logical step applied, in order
+    pub steps: Vec<Step>,
+    /// Techniques reasoning about Killer cage sums that were used, in order of first use
+    pub cage_techniques: Vec<String>,
 }
 
 impl SolverStats {
@@ -17,32 +30,114 @@ impl SolverStats {
         Self {
             strategies_used: HashMap::new(),
             cells_filled: 0,
+            candidates_eliminated: 0,
             iterations: 0,
             backtrack_steps: 0,
+            steps: Vec::new(),
+            cage_techniques: Vec::new(),
         }
     }
 }
 
+/// How many solutions a puzzle has
+#[derive(Debug, Clone)]
+// Solutions are handed out whole, so `Multiple` is twice the size of `Unique`
+#[allow(clippy::large_enum_variant)]
+pub enum Uniqueness {
+    NoSolution,
+    Unique(Sudoku),
+    /// Two differing solutions, witnessing that the puzzle is ambiguous
+    Multiple(Sudoku, Sudoku),
+}
+
+/// Whether a puzzle has exactly one solution, and which of its givens are needed for that
+#[derive(Debug, Clone)]
+pub struct PuzzleAnalysis {
+    pub uniqueness: Uniqueness,
+    /// Givens that can be removed with the solution staying unique, in row-major order;
+    /// empty unless the solution is unique
+    pub redundant_givens: Vec<(usize, usize)>,
+}
+
+impl PuzzleAnalysis {
+    pub fn is_unique(&self) -> bool {
+        matches!(self.uniqueness, Uniqueness::Unique(_))
+    }
+
+    /// Unique, and no given can be removed without losing that
+    pub fn is_minimal(&self) -> bool {
+        self.is_unique() && self.redundant_givens.is_empty()
+    }
+
+    /// Cells where the two witness solutions differ; empty unless there are several solutions
+    pub fn ambiguous_cells(&self) -> Vec<(usize, usize)> {
+        match &self.uniqueness {
+            Uniqueness::Multiple(first, second) => (0..first.size)
+                .flat_map(|row| (0..first.size).map(move |col| (row, col)))
+                .filter(|&(row, col)| first.cell(row, col).value() != second.cell(row, col).value())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// How hard a puzzle is to solve with logic alone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grade {
+    /// Rating of the hardest technique needed, on the Sudoku Explainer scale
+    pub rating: f32,
+    pub difficulty: Difficulty,
+    /// The hardest technique needed; `None` if there was nothing to solve
+    pub hardest_technique: Option<String>,
+    /// Every technique used, in order of first use
+    pub techniques: Vec<String>,
+    /// Logical steps taken to solve the puzzle
+    pub steps: usize,
+    /// Whether the strategies solved the puzzle; puzzles they cannot finish are Expert
+    pub solved: bool,
+}
+
+/// How the solver searches for solutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolverBackend {
+    /// Logical strategies first, then backtracking for whatever they leave
+    #[default]
+    Backtracking,
+    /// Dancing Links exact-cover search, skipping the logical strategies
+    DancingLinks,
+}
+
+/// Cloning is cheap: the clones share the strategies and copy the settings
+#[derive(Clone)]
 pub struct SudokuSolver {
-    strategies: Vec<Box<dyn SolvingStrategy>>,
+    strategies: Vec<Arc<dyn SolvingStrategy>>,
     max_iterations: usize,
     use_backtracking: bool,
+    assume_unique_solution: bool,
+    backend: SolverBackend,
+    time_limit: Option<Duration>,
 }
 
 impl SudokuSolver {
     pub fn new() -> Self {
         Self {
-            strategies: get_all_strategies(),
+            strategies: get_all_strategies().into_iter().map(Arc::from).collect(),
             max_iterations: 1000,
             use_backtracking: true,
+            assume_unique_solution: false,
+            backend: SolverBackend::default(),
+            time_limit: None,
         }
     }
 
     pub fn new_with_strategies(strategies: Vec<Box<dyn SolvingStrategy>>) -> Self {
         Self {
-            strategies,
+            strategies: strategies.into_iter().map(Arc::from).collect(),
             max_iterations: 1000,
             use_backtracking: true,
+            assume_unique_solution: false,
+            backend: SolverBackend::default(),
+            time_limit: None,
         }
     }
 
@@ -54,72 +149,118 @@ impl SudokuSolver {
         self.use_backtracking = use_backtracking;
     }
 
-    pub fn solve(&mut self, mut sudoku: Sudoku) -> Result<Sudoku, String> {
-        if !sudoku.is_valid() {
-            return Err("Invalid initial state".to_string());
-        }
+    /// Assert that puzzles given to the solver have exactly one solution, enabling
+    /// uniqueness strategies such as Unique Rectangles and BUG+1
+    pub fn set_assume_unique_solution(&mut self, assume_unique_solution: bool) {
+        self.assume_unique_solution = assume_unique_solution;
+    }
 
-        let mut stats = SolverStats::new();
+    /// Choose how `solve`, `count_solutions` and `find_solutions` search; hints and steps
+    /// always come from the logical strategies
+    pub fn set_backend(&mut self, backend: SolverBackend) {
+        self.backend = backend;
+    }
 
-        // First, try logical strategies
-        if self.solve_with_strategies(&mut sudoku, &mut stats) {
-            return Ok(sudoku);
-        }
+    pub fn backend(&self) -> SolverBackend {
+        self.backend
+    }
 
-        // If logical strategies aren't enough, use backtracking
-        if self.use_backtracking {
-            if self.solve_with_backtracking(&mut sudoku, &mut stats) {
-                return Ok(sudoku);
-            }
-        }
+    /// Give up solving with `SudokuError::TimeLimit` after `time_limit` (not available on
+    /// WebAssembly, which has no clock)
+    pub fn set_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.time_limit = time_limit;
+    }
 
-        if sudoku.is_complete() && sudoku.is_valid() {
-            Ok(sudoku)
-        } else {
-            Err("No solution found".to_string())
-        }
+    /// Strategies allowed under the current settings
+    fn active_strategies(&self) -> impl Iterator<Item = &Arc<dyn SolvingStrategy>> {
+        self.strategies
+            .iter()
+            .filter(|strategy| self.assume_unique_solution || !strategy.requires_uniqueness())
+    }
+
+    pub fn solve(&mut self, sudoku: Sudoku) -> Result<Sudoku, SudokuError> {
+        self.solve_with_stats(sudoku).map(|(solution, _)| solution)
     }
 
     pub fn solve_with_stats(
         &mut self,
         mut sudoku: Sudoku,
-    ) -> Result<(Sudoku, SolverStats), String> {
+    ) -> Result<(Sudoku, SolverStats), SudokuError> {
         if !sudoku.is_valid() {
-            return Err("Invalid initial state".to_string());
+            return Err(SudokuError::ContradictoryGivens {
+                cells: sudoku.conflicting_cells(),
+            });
         }
 
         let mut stats = SolverStats::new();
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+
+        if self.backend == SolverBackend::DancingLinks {
+            let solution = self.solve_with_dancing_links(&sudoku, &mut stats, deadline)?;
+            return Ok((solution, stats));
+        }
 
         // First, try logical strategies
-        if self.solve_with_strategies(&mut sudoku, &mut stats) {
+        if self.solve_with_strategies(&mut sudoku, &mut stats, deadline)? {
             return Ok((sudoku, stats));
         }
 
         // If logical strategies aren't enough, use backtracking
         if self.use_backtracking {
-            if self.solve_with_backtracking(&mut sudoku, &mut stats) {
+            if self.solve_with_backtracking(&mut sudoku, &mut stats, deadline)? {
                 return Ok((sudoku, stats));
             }
+        } else if stats.iterations >= self.max_iterations {
+            return Err(SudokuError::IterationLimit(self.max_iterations));
         }
 
-        if sudoku.is_complete() && sudoku.is_valid() {
-            Ok((sudoku, stats))
-        } else {
-            Err("No solution found".to_string())
+        Err(SudokuError::Unsolvable)
+    }
+
+    /// Error out once the solver's time limit has passed
+    fn check_deadline(&self, deadline: Option<Instant>) -> Result<(), SudokuError> {
+        match (deadline, self.time_limit) {
+            (Some(deadline), Some(limit)) if Instant::now() >= deadline => {
+                Err(SudokuError::TimeLimit(limit))
+            }
+            _ => Ok(()),
         }
     }
 
-    fn solve_with_strategies(&self, sudoku: &mut Sudoku, stats: &mut SolverStats) -> bool {
+    fn solve_with_strategies(
+        &self,
+        sudoku: &mut Sudoku,
+        stats: &mut SolverStats,
+        deadline: Option<Instant>,
+    ) -> Result<bool, SudokuError> {
         let mut progress = true;
 
         while progress && !sudoku.is_complete() && stats.iterations < self.max_iterations {
+            self.check_deadline(deadline)?;
             progress = false;
             stats.iterations += 1;
 
-            for strategy in &self.strategies {
+            for strategy in self.active_strategies() {
+                let steps = strategy.find_steps(sudoku);
+                if steps.is_empty() {
+                    continue;
+                }
+
                 let initial_empty_count = self.count_empty_cells(sudoku);
+                let mut applied = false;
+                for step in steps {
+                    stats.candidates_eliminated += step
+                        .eliminations
+                        .iter()
+                        .filter(|e| sudoku.has_candidate(e.row, e.col, e.value))
+                        .count();
+                    if step.apply(sudoku) {
+                        applied = true;
+                        stats.steps.push(step);
+                    }
+                }
 
-                if strategy.apply(sudoku) {
+                if applied {
                     let final_empty_count = self.count_empty_cells(sudoku);
                     let cells_filled = initial_empty_count - final_empty_count;
 
@@ -128,116 +269,210 @@ impl SudokuSolver {
                         .strategies_used
                         .entry(strategy.name().to_string())
                         .or_insert(0) += 1;
+                    if strategy.uses_cages()
+                        && !stats
+                            .cage_techniques
+                            .iter()
+                            .any(|name| name == strategy.name())
+                    {
+                        stats.cage_techniques.push(strategy.name().to_string());
+                    }
 
                     progress = true;
 
                     if !sudoku.is_valid() {
-                        return false;
+                        return Ok(false);
                     }
+
+                    // Go back to the simplest strategies before trying expensive ones again
+                    break;
                 }
             }
         }
 
-        sudoku.is_complete() && sudoku.is_valid()
+        Ok(sudoku.is_complete() && sudoku.is_valid())
     }
 
-    fn solve_with_backtracking(&self, sudoku: &mut Sudoku, stats: &mut SolverStats) -> bool {
-        if sudoku.is_complete() {
-            return sudoku.is_valid();
+    /// Exact-cover search for the first solution; each placement tried counts as a
+    /// backtrack step
+    fn solve_with_dancing_links(
+        &self,
+        sudoku: &Sudoku,
+        stats: &mut SolverStats,
+        deadline: Option<Instant>,
+    ) -> Result<Sudoku, SudokuError> {
+        let mut links = DancingLinks::new(sudoku);
+        links.set_deadline(deadline);
+        let mut solution = None;
+        links.search(&mut |choices| {
+            let mut grid = sudoku.clone();
+            for &(row, col, value) in choices {
+                grid.assign(row, col, value);
+            }
+            grid.reset_candidates();
+            solution = Some(grid);
+            false
+        });
+        stats.backtrack_steps += links.nodes();
+
+        match (solution, self.time_limit) {
+            (Some(solution), _) => Ok(solution),
+            (None, Some(limit)) if links.stopped() => Err(SudokuError::TimeLimit(limit)),
+            (None, _) => Err(SudokuError::Unsolvable),
         }
+    }
 
-        // Find the empty cell with the fewest candidates (MRV heuristic)
-        let (row, col) = match self.find_best_empty_cell(sudoku) {
-            Some(pos) => pos,
-            None => return sudoku.is_valid(),
-        };
-
-        let candidates = sudoku.get_candidates(row, col);
+    fn solve_with_backtracking(
+        &self,
+        sudoku: &mut Sudoku,
+        stats: &mut SolverStats,
+        deadline: Option<Instant>,
+    ) -> Result<bool, SudokuError> {
+        let solved = self.backtrack(sudoku, stats, deadline);
+        // The search places values without maintaining pencil marks
+        sudoku.reset_candidates();
+        solved
+    }
 
-        for &value in &candidates {
-            if sudoku.set(row, col, value).is_ok() {
-                stats.backtrack_steps += 1;
+    fn backtrack(
+        &self,
+        sudoku: &mut Sudoku,
+        stats: &mut SolverStats,
+        deadline: Option<Instant>,
+    ) -> Result<bool, SudokuError> {
+        let choices = match self.choose_branch(sudoku) {
+            Some(choices) => choices,
+            None => return Ok(sudoku.is_valid()),
+        };
 
-                if sudoku.is_valid() {
-                    if self.solve_with_backtracking(sudoku, stats) {
-                        return true;
-                    }
-                }
+        for (row, col, value) in choices {
+            sudoku.assign(row, col, value);
+            stats.backtrack_steps += 1;
+            if stats.backtrack_steps % 1024 == 1 {
+                self.check_deadline(deadline)?;
+            }
 
-                // Backtrack
-                sudoku.set(row, col, 0).unwrap();
+            if self.backtrack(sudoku, stats, deadline)? {
+                return Ok(true);
             }
+
+            sudoku.unassign(row, col);
         }
 
-        false
+        Ok(false)
     }
 
-    fn find_best_empty_cell(&self, sudoku: &Sudoku) -> Option<(usize, usize)> {
+    /// The placements to try next during a search, or `None` once the grid is full. This is
+    /// either the values of the empty cell with the fewest of them (MRV heuristic) or the
+    /// places of the value with the fewest places left in a house, whichever is shorter; an
+    /// empty list means the grid has no solution.
+    ///
+    /// Pencil marks are only ever narrowed by sound eliminations, so they are combined with
+    /// the values still free in each cell's units and those its cage can still take.
+    fn choose_branch(&self, sudoku: &Sudoku) -> Option<Vec<(usize, usize, u8)>> {
+        let size = sudoku.size;
+        let layout = sudoku.layout();
+        let mask_at = |row: usize, col: usize| {
+            sudoku.candidate_mask(row, col)
+                & sudoku.allowed_mask(row, col)
+                & sudoku.cage_mask(row, col)
+        };
+
         let mut best_cell = None;
-        let mut min_candidates = usize::MAX;
-
-        for row in 0..sudoku.size {
-            for col in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    let candidates = sudoku.get_candidates(row, col);
-                    if candidates.len() < min_candidates {
-                        min_candidates = candidates.len();
-                        best_cell = Some((row, col));
-
-                        // If we find a cell with no candidates, return immediately
-                        if min_candidates == 0 {
-                            return best_cell;
-                        }
+        let mut min_choices = usize::MAX;
+
+        for row in 0..size {
+            for col in 0..size {
+                if !sudoku.cell(row, col).is_empty() {
+                    continue;
+                }
+                let mask = mask_at(row, col);
+                if (mask.count_ones() as usize) < min_choices {
+                    min_choices = mask.count_ones() as usize;
+                    best_cell = Some((row, col, mask));
+                    if min_choices <= 1 {
+                        break;
                     }
                 }
             }
+            if min_choices <= 1 {
+                break;
+            }
         }
 
-        best_cell
-    }
+        let (row, col, mask) = best_cell?;
+        let mut choices: Vec<(usize, usize, u8)> =
+            mask_values(mask).map(|value| (row, col, value)).collect();
+        if min_choices <= 1 {
+            return Some(choices);
+        }
 
-    fn count_empty_cells(&self, sudoku: &Sudoku) -> usize {
-        let mut count = 0;
-        for row in 0..sudoku.size {
-            for col in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    count += 1;
+        let mut places = vec![0usize; size];
+        for unit in 0..layout.units().len() {
+            let cells = || {
+                layout
+                    .cells_of(unit)
+                    .iter()
+                    .map(|&cell| (cell / size, cell % size))
+            };
+            places.iter_mut().for_each(|count| *count = 0);
+            let mut placed = 0u32;
+            for (row, col) in cells() {
+                match sudoku.cell(row, col).value() {
+                    Some(value) => placed |= value_bit(value),
+                    None => mask_values(mask_at(row, col))
+                        .for_each(|value| places[value as usize - 1] += 1),
                 }
             }
-        }
-        count
-    }
 
-    pub fn get_hint(&mut self, sudoku: &mut Sudoku) -> Option<(usize, usize, u8)> {
-        // Try to find a cell that can be filled using logical strategies
-        for row in 0..sudoku.size {
-            for col in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    let candidates = sudoku.get_candidates(row, col);
-                    if candidates.len() == 1 {
-                        let value = *candidates.iter().next().unwrap();
-                        return Some((row, col, value));
-                    }
+            for value in mask_values(full_mask(size) & !placed) {
+                let count = places[value as usize - 1];
+                if count >= choices.len() {
+                    continue;
+                }
+                choices = cells()
+                    .filter(|&(row, col)| {
+                        sudoku.cell(row, col).is_empty()
+                            && mask_at(row, col) & value_bit(value) != 0
+                    })
+                    .map(|(row, col)| (row, col, value))
+                    .collect();
+                if count <= 1 {
+                    return Some(choices);
                 }
             }
         }
 
-        // If no naked singles, try hidden singles
-        for strategy in &self.strategies {
-            if strategy.name() == "Hidden Singles" {
-                let mut temp_sudoku = sudoku.clone();
-                if strategy.apply(&mut temp_sudoku) {
-                    // Find the difference
-                    for row in 0..sudoku.size {
-                        for col in 0..sudoku.size {
-                            if sudoku.grid[row][col] != temp_sudoku.grid[row][col] {
-                                if let Some(value) = temp_sudoku.grid[row][col].value() {
-                                    return Some((row, col, value));
-                                }
-                            }
-                        }
-                    }
-                }
+        Some(choices)
+    }
+
+    fn count_empty_cells(&self, sudoku: &Sudoku) -> usize {
+        sudoku.empty_cells()
+    }
+
+    /// The first deduction the solver's strategies can make on the grid, in strategy order
+    pub fn next_step(&self, sudoku: &Sudoku) -> Option<Step> {
+        self.active_strategies()
+            .find_map(|strategy| strategy.find_step(sudoku))
+    }
+
+    pub fn get_hint(&mut self, sudoku: &mut Sudoku) -> Option<(usize, usize, u8)> {
+        self.hint_step(sudoku).map(|step| {
+            let placement = step.placements[0];
+            (placement.row, placement.col, placement.value)
+        })
+    }
+
+    /// The first step that places a value, found after making any eliminations needed to
+    /// reach it on a copy of the grid
+    pub fn hint_step(&self, sudoku: &Sudoku) -> Option<Step> {
+        let mut grid = sudoku.clone();
+        while let Some(step) = self.next_step(&grid) {
+            if !step.placements.is_empty() {
+                return Some(step);
+            }
+            if !step.apply(&mut grid) {
+                break;
             }
         }
 
@@ -248,102 +483,373 @@ impl SudokuSolver {
         sudoku.is_complete() && sudoku.is_valid()
     }
 
-    pub fn count_solutions(&mut self, mut sudoku: Sudoku, max_solutions: usize) -> usize {
+    pub fn count_solutions(&mut self, sudoku: Sudoku, max_solutions: usize) -> usize {
         let mut count = 0;
-        self.count_solutions_recursive(&mut sudoku, &mut count, max_solutions);
+        if max_solutions > 0 {
+            self.for_each_solution(sudoku, &mut |_| {
+                count += 1;
+                count < max_solutions
+            });
+        }
         count
     }
 
-    fn count_solutions_recursive(
+    /// Up to `max_solutions` solutions of the grid, in search order; pass `usize::MAX` to
+    /// enumerate all of them
+    pub fn find_solutions(&mut self, sudoku: Sudoku, max_solutions: usize) -> Vec<Sudoku> {
+        let mut solutions = Vec::new();
+        if max_solutions > 0 {
+            self.for_each_solution(sudoku, &mut |solution| {
+                let mut solution = solution.clone();
+                solution.reset_candidates();
+                solutions.push(solution);
+                solutions.len() < max_solutions
+            });
+        }
+        solutions
+    }
+
+    /// Solve with the logical strategies only, each time taking a step with the easiest
+    /// technique available, and rate the puzzle by the hardest technique it needed
+    pub fn grade(&self, sudoku: &Sudoku) -> Result<Grade, SudokuError> {
+        self.grade_without(sudoku, &[])
+    }
+
+    /// Grade the grid without the strategies named in `forbidden`, as the generator grades
+    /// puzzles made with [`GenerationOptions::forbidden_techniques`]
+    pub fn grade_without(
         &self,
-        sudoku: &mut Sudoku,
-        count: &mut usize,
-        max_solutions: usize,
-    ) {
-        if *count >= max_solutions {
-            return;
+        sudoku: &Sudoku,
+        forbidden: &[String],
+    ) -> Result<Grade, SudokuError> {
+        if !sudoku.is_valid() {
+            return Err(SudokuError::ContradictoryGivens {
+                cells: sudoku.conflicting_cells(),
+            });
+        }
+
+        let mut strategies: Vec<&dyn SolvingStrategy> = self
+            .active_strategies()
+            .filter(|strategy| !is_named(strategy.as_ref(), forbidden))
+            .map(|strategy| strategy.as_ref())
+            .collect();
+        strategies.sort_by(|a, b| a.rating().total_cmp(&b.rating()));
+
+        let mut grid = sudoku.clone();
+        let mut hardest: Option<&dyn SolvingStrategy> = None;
+        let mut rating = 0.0;
+        let mut techniques: Vec<String> = Vec::new();
+        let mut steps = 0;
+        let mut progress = true;
+        while progress && !grid.is_complete() && grid.is_valid() {
+            progress = false;
+            for &strategy in &strategies {
+                if let Some(step) = strategy.find_step(&grid) {
+                    if step.apply(&mut grid) {
+                        steps += 1;
+                        if !techniques.iter().any(|name| name == strategy.name()) {
+                            techniques.push(strategy.name().to_string());
+                        }
+                        if strategy.rating() > rating {
+                            rating = strategy.rating();
+                            hardest = Some(strategy);
+                        }
+                        progress = true;
+                        break;
+                    }
+                }
+            }
         }
 
-        if sudoku.is_complete() {
-            if sudoku.is_valid() {
-                *count += 1;
+        let solved = grid.is_complete() && grid.is_valid();
+        Ok(Grade {
+            rating,
+            difficulty: if solved {
+                Difficulty::from_rating(rating)
+            } else {
+                Difficulty::Expert
+            },
+            hardest_technique: hardest.map(|strategy| strategy.name().to_string()),
+            techniques,
+            steps,
+            solved,
+        })
+    }
+
+    /// Whether the strategies no harder than `difficulty`, less those named in `forbidden`,
+    /// solve the grid on their own
+    fn solvable_within(
+        &self,
+        sudoku: &Sudoku,
+        difficulty: Difficulty,
+        forbidden: &[String],
+    ) -> bool {
+        let strategies: Vec<&dyn SolvingStrategy> = self
+            .active_strategies()
+            .filter(|strategy| Difficulty::from_rating(strategy.rating()) <= difficulty)
+            .filter(|strategy| !is_named(strategy.as_ref(), forbidden))
+            .map(|strategy| strategy.as_ref())
+            .collect();
+
+        let mut grid = sudoku.clone();
+        let mut progress = true;
+        while progress && !grid.is_complete() && grid.is_valid() {
+            progress = strategies.iter().any(|strategy| strategy.apply(&mut grid));
+        }
+        grid.is_complete() && grid.is_valid()
+    }
+
+    /// Check whether the grid has no, one or several solutions, and for a unique one which
+    /// givens could be removed
+    pub fn analyze(&mut self, sudoku: &Sudoku) -> PuzzleAnalysis {
+        let mut solutions = self.find_solutions(sudoku.clone(), 2).into_iter();
+        let uniqueness = match (solutions.next(), solutions.next()) {
+            (None, _) => Uniqueness::NoSolution,
+            (Some(solution), None) => Uniqueness::Unique(solution),
+            (Some(first), Some(second)) => Uniqueness::Multiple(first, second),
+        };
+
+        let mut redundant_givens = Vec::new();
+        if matches!(uniqueness, Uniqueness::Unique(_)) {
+            for row in 0..sudoku.size {
+                for col in 0..sudoku.size {
+                    if !sudoku.cell(row, col).is_empty() {
+                        let mut reduced = sudoku.clone();
+                        reduced.set(row, col, 0).unwrap();
+                        if still_unique(&reduced, &[(row, col)], sudoku, None) == Some(true) {
+                            redundant_givens.push((row, col));
+                        }
+                    }
+                }
             }
+        }
+
+        PuzzleAnalysis {
+            uniqueness,
+            redundant_givens,
+        }
+    }
+
+    /// Call `found` with each solution of the grid until it returns `false`, using the
+    /// selected backend; pencil marks of the solutions passed may be stale
+    fn for_each_solution(&self, mut sudoku: Sudoku, found: &mut dyn FnMut(&Sudoku) -> bool) {
+        if !sudoku.is_valid() {
             return;
         }
 
-        let (row, col) = match sudoku.find_empty_cell() {
-            Some(pos) => pos,
-            None => return,
-        };
+        match self.backend {
+            SolverBackend::Backtracking => {
+                self.enumerate_solutions(&mut sudoku, found);
+            }
+            SolverBackend::DancingLinks => {
+                DancingLinks::new(&sudoku).search(&mut |choices| {
+                    let mut solution = sudoku.clone();
+                    for &(row, col, value) in choices {
+                        solution.assign(row, col, value);
+                    }
+                    found(&solution)
+                });
+            }
+        }
+    }
 
-        let candidates = sudoku.get_candidates(row, col);
+    /// Backtracking search for every solution; returns `false` once `found` asks to stop
+    fn enumerate_solutions(
+        &self,
+        sudoku: &mut Sudoku,
+        found: &mut dyn FnMut(&Sudoku) -> bool,
+    ) -> bool {
+        let choices = match self.choose_branch(sudoku) {
+            Some(choices) => choices,
+            None => return !sudoku.is_valid() || found(sudoku),
+        };
 
-        for &value in &candidates {
-            if sudoku.set(row, col, value).is_ok() {
-                if sudoku.is_valid() {
-                    self.count_solutions_recursive(sudoku, count, max_solutions);
-                }
-                sudoku.set(row, col, 0).unwrap();
+        for (row, col, value) in choices {
+            sudoku.assign(row, col, value);
+            let keep_going = self.enumerate_solutions(sudoku, found);
+            sudoku.unassign(row, col);
+            if !keep_going {
+                return false;
             }
         }
+
+        true
     }
 
+    /// Generate a puzzle with a unique solution whose hardest technique matches `difficulty`,
+    /// or the hardest found below it if none does within a few attempts
     pub fn generate_puzzle(
         &mut self,
         size: usize,
         difficulty: Difficulty,
-    ) -> Result<Sudoku, String> {
-        let mut sudoku = Sudoku::new(size);
-        let mut rng = thread_rng();
+    ) -> Result<Sudoku, SudokuError> {
+        self.generate_with_options(size, &GenerationOptions::new(difficulty))
+    }
 
-        // Fill the diagonal boxes first (they don't interfere with each other)
-        // Randomize the order of filling diagonal boxes for more variety
-        let box_size = sudoku.box_size;
-        let mut diagonal_indices: Vec<usize> = (0..box_size).collect();
-        diagonal_indices.shuffle(&mut rng);
+    /// Generate a puzzle with a unique solution that meets `options`; if none reaches their
+    /// difficulty within a few attempts, the hardest found below it that meets the rest.
+    /// The same seed in `options` always gives the same puzzle
+    pub fn generate_with_options(
+        &mut self,
+        size: usize,
+        options: &GenerationOptions,
+    ) -> Result<Sudoku, SudokuError> {
+        match options.seed {
+            Some(seed) => self.generate_with_rng(size, options, &mut StdRng::seed_from_u64(seed)),
+            None => self.generate_with_rng(size, options, &mut thread_rng()),
+        }
+    }
 
-        for &i in &diagonal_indices {
-            self.fill_box(&mut sudoku, i * box_size, i * box_size)?;
+    /// Generate a puzzle as [`generate_with_options`](Self::generate_with_options) does, drawing
+    /// every random choice from `rng` and ignoring the seed in `options`
+    pub fn generate_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        size: usize,
+        options: &GenerationOptions,
+        rng: &mut R,
+    ) -> Result<Sudoku, SudokuError> {
+        for name in options
+            .required_techniques
+            .iter()
+            .chain(&options.forbidden_techniques)
+        {
+            if !self
+                .strategies
+                .iter()
+                .any(|strategy| is_named(strategy.as_ref(), std::slice::from_ref(name)))
+            {
+                return Err(SudokuError::UnknownTechnique(name.clone()));
+            }
+        }
+        if let (Some(min), Some(max)) = (options.min_clues, options.max_clues) {
+            if min > max {
+                return Err(SudokuError::GenerationFailed(format!(
+                    "at least {} clues asked for, but at most {}",
+                    min, max
+                )));
+            }
+        }
+        if options.cage_size == Some(0) {
+            return Err(SudokuError::GenerationFailed(
+                "cages need at least one cell".to_string(),
+            ));
         }
 
-        // Solve the complete puzzle
-        let full_solution = self.solve(sudoku.clone())?;
+        let mut closest: Option<(Difficulty, Sudoku)> = None;
+        for _ in 0..GENERATION_ATTEMPTS {
+            let mut solution = self.random_solution(size, options, rng)?;
+            if let Some(max_size) = options.cage_size {
+                let mut layout = solution.layout().clone();
+                for cage in random_cages(&solution, max_size, rng) {
+                    layout.add_cage(cage)?;
+                }
+                solution = Sudoku::from_string_with_layout(&solution.to_line(), layout)?;
+            }
+            let puzzle = self.remove_cells(solution, options, rng)?;
+            let grade = self.grade_without(&puzzle, &options.forbidden_techniques)?;
+
+            let too_many_clues =
+                matches!(options.max_clues, Some(max) if puzzle.clue_count() > max);
+            let has_required = options.required_techniques.iter().all(|required| {
+                grade
+                    .techniques
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(required))
+            });
+            if too_many_clues || !has_required {
+                continue;
+            }
 
-        // Remove cells based on difficulty with some randomization
-        let base_cells_to_remove = match difficulty {
-            Difficulty::Easy => size * size * 40 / 100, // Remove 40%
-            Difficulty::Medium => size * size * 50 / 100, // Remove 50%
-            Difficulty::Hard => size * size * 60 / 100, // Remove 60%
-            Difficulty::Expert => size * size * 70 / 100, // Remove 70%
-        };
+            if grade.difficulty == options.difficulty {
+                return Ok(puzzle);
+            }
+            if !matches!(closest, Some((best, _)) if best >= grade.difficulty) {
+                closest = Some((grade.difficulty, puzzle));
+            }
+        }
 
-        // Add some randomization to the number of cells removed (±5%)
-        let variation = (base_cells_to_remove as f32 * 0.05) as usize;
-        let cells_to_remove = if variation > 0 {
-            let min_remove = base_cells_to_remove.saturating_sub(variation);
-            let max_remove = base_cells_to_remove + variation;
-            rng.gen_range(min_remove..=max_remove.min(size * size - 17)) // Ensure at least 17 clues
-        } else {
-            base_cells_to_remove
-        };
+        closest.map(|(_, puzzle)| puzzle).ok_or_else(|| {
+            SudokuError::GenerationFailed(format!(
+                "no puzzle met the options in {} attempts",
+                GENERATION_ATTEMPTS
+            ))
+        })
+    }
 
-        self.remove_cells_symmetrically(full_solution, cells_to_remove)
+    /// A random complete grid, following the boxes and variant rules of `options`
+    fn random_solution<R: Rng + ?Sized>(
+        &mut self,
+        size: usize,
+        options: &GenerationOptions,
+        rng: &mut R,
+    ) -> Result<Sudoku, SudokuError> {
+        let layout = options.layout(size)?;
+        if !options.variants.is_empty() {
+            // Boxes filled on their own may break the variant rules, so fill every cell
+            // by a search trying values in random order
+            for _ in 0..GENERATION_ATTEMPTS {
+                let mut sudoku = Sudoku::with_layout(layout.clone());
+                let mut nodes = RANDOM_FILL_NODES;
+                if fill_randomly(&mut sudoku, rng, &mut nodes) {
+                    sudoku.reset_candidates();
+                    return Ok(sudoku);
+                }
+            }
+            return Err(SudokuError::GenerationFailed(
+                "could not complete the grid".to_string(),
+            ));
+        }
+
+        // On 4x4 grids, some fillings of the diagonal boxes cannot be completed
+        for _ in 0..GENERATION_ATTEMPTS {
+            let mut sudoku = Sudoku::with_layout(layout.clone());
+
+            // Fill the diagonal boxes first (they don't interfere with each other)
+            // Randomize the order of filling diagonal boxes for more variety
+            let (box_rows, box_cols) = (sudoku.box_rows, sudoku.box_cols);
+            let mut diagonal_indices: Vec<usize> = (0..box_rows.min(box_cols)).collect();
+            diagonal_indices.shuffle(rng);
+
+            for &i in &diagonal_indices {
+                self.fill_box(&mut sudoku, i * box_rows, i * box_cols, rng)?;
+            }
+
+            // Solve the complete puzzle; the partly filled grid has many solutions
+            let assume_unique_solution = std::mem::replace(&mut self.assume_unique_solution, false);
+            let full_solution = self.solve(sudoku);
+            self.assume_unique_solution = assume_unique_solution;
+            match full_solution {
+                Ok(solution) => return Ok(solution),
+                Err(SudokuError::Unsolvable) => continue,
+                Err(e) => {
+                    return Err(SudokuError::GenerationFailed(format!(
+                        "could not complete the grid: {}",
+                        e
+                    )))
+                }
+            }
+        }
+
+        Err(SudokuError::GenerationFailed(
+            "could not complete the grid".to_string(),
+        ))
     }
 
-    fn fill_box(
+    fn fill_box<R: Rng + ?Sized>(
         &self,
         sudoku: &mut Sudoku,
         start_row: usize,
         start_col: usize,
-    ) -> Result<(), String> {
+        rng: &mut R,
+    ) -> Result<(), SudokuError> {
         let mut values: Vec<u8> = (1..=sudoku.size as u8).collect();
 
         // Shuffle values randomly
-        values.shuffle(&mut thread_rng());
+        values.shuffle(rng);
 
         let mut idx = 0;
-        for row in start_row..start_row + sudoku.box_size {
-            for col in start_col..start_col + sudoku.box_size {
+        for row in start_row..start_row + sudoku.box_rows {
+            for col in start_col..start_col + sudoku.box_cols {
                 sudoku.set(row, col, values[idx])?;
                 idx += 1;
             }
@@ -352,14 +858,19 @@ impl SudokuSolver {
         Ok(())
     }
 
-    fn remove_cells_symmetrically(
+    /// Empty as many cells of a solved grid as possible, following the symmetry, while the
+    /// solution stays unique, the clues within range and the puzzle no harder than asked
+    /// for; then turn the rest into givens
+    fn remove_cells<R: Rng + ?Sized>(
         &self,
-        mut sudoku: Sudoku,
-        cells_to_remove: usize,
-    ) -> Result<Sudoku, String> {
-        let mut removed = 0;
-        let size = sudoku.size;
-        let mut rng = thread_rng();
+        solution: Sudoku,
+        options: &GenerationOptions,
+        rng: &mut R,
+    ) -> Result<Sudoku, SudokuError> {
+        let size = solution.size;
+        let mut sudoku = solution.clone();
+        let mut clues = size * size;
+        let min_clues = options.min_clues.unwrap_or(0);
 
         // Create a list of all cell positions
         let mut positions: Vec<(usize, usize)> = (0..size)
@@ -367,54 +878,195 @@ impl SudokuSolver {
             .collect();
 
         // Shuffle the positions randomly
-        positions.shuffle(&mut rng);
+        positions.shuffle(rng);
 
-        // Remove cells randomly while maintaining some symmetry
         for &(row, col) in &positions {
-            if removed >= cells_to_remove {
-                break;
+            // Symmetric cells are emptied together, so either all are still filled or none
+            if sudoku.cell(row, col).is_empty() {
+                continue;
+            }
+            let cells = options.symmetry.orbit(row, col, size);
+            if clues < min_clues + cells.len() {
+                continue;
             }
 
-            // Remove current cell
-            if sudoku.grid[row][col] != Cell::Empty {
-                sudoku.grid[row][col] = Cell::Empty;
-                removed += 1;
+            let mut candidate = sudoku.clone();
+            for &(row, col) in &cells {
+                candidate.set(row, col, 0)?;
+            }
 
-                // Optionally remove symmetric cell (not always for more variety)
-                if removed < cells_to_remove && rng.gen_bool(0.7) {
-                    let sym_row = size - 1 - row;
-                    let sym_col = size - 1 - col;
-                    if sym_row != row || sym_col != col {
-                        if sudoku.grid[sym_row][sym_col] != Cell::Empty {
-                            sudoku.grid[sym_row][sym_col] = Cell::Empty;
-                            removed += 1;
-                        }
-                    }
-                }
+            // Back out removals that would let the puzzle have another solution, or that
+            // could not be checked within the search budget
+            if still_unique(&candidate, &cells, &solution, Some(UNIQUENESS_CHECK_NODES))
+                != Some(true)
+            {
+                continue;
             }
+
+            // Back out removals that make the puzzle harder than asked for
+            if options.difficulty < Difficulty::Expert
+                && !self.solvable_within(
+                    &candidate,
+                    options.difficulty,
+                    &options.forbidden_techniques,
+                )
+            {
+                continue;
+            }
+
+            sudoku = candidate;
+            clues -= cells.len();
         }
 
+        sudoku.mark_givens();
         Ok(sudoku)
     }
 
-    pub fn solve_step(&self, sudoku: &mut Sudoku) -> bool {
-        for strategy in &self.strategies {
-            if strategy.apply(sudoku) {
-                return true;
+    /// Apply the next logical step to the grid, returning it if there was one
+    pub fn solve_step(&self, sudoku: &mut Sudoku) -> Option<Step> {
+        let step = self.next_step(sudoku)?;
+        step.apply(sudoku);
+        Some(step)
+    }
+}
+
+/// Whether the strategy is one of the `names`, ignoring case
+fn is_named(strategy: &dyn SolvingStrategy, names: &[String]) -> bool {
+    names
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(strategy.name()))
+}
+
+/// Complete grids the generator tries before settling for the closest difficulty
+const GENERATION_ATTEMPTS: usize = 20;
+
+/// Placements each uniqueness check of the generator may try before giving up
+const UNIQUENESS_CHECK_NODES: usize = 10_000;
+
+/// Placements each attempt to fill a grid at random may try before starting again
+const RANDOM_FILL_NODES: usize = 100_000;
+
+/// Fill the empty cells of a grid by a search trying values in random order, placing at
+/// most `nodes` values; `false` if the grid could not be completed within them
+fn fill_randomly<R: Rng + ?Sized>(sudoku: &mut Sudoku, rng: &mut R, nodes: &mut usize) -> bool {
+    // Branch on the empty cell with the fewest values left
+    let mut branch: Option<(usize, usize, u32)> = None;
+    for row in 0..sudoku.size {
+        for col in 0..sudoku.size {
+            if !sudoku.cell(row, col).is_empty() {
+                continue;
             }
+            let mask = sudoku.allowed_mask(row, col);
+            match branch {
+                Some((_, _, best)) if best.count_ones() <= mask.count_ones() => {}
+                _ => branch = Some((row, col, mask)),
+            }
+        }
+    }
+    let (row, col, mask) = match branch {
+        Some(branch) => branch,
+        None => return true,
+    };
+
+    let mut values: Vec<u8> = mask_values(mask).collect();
+    values.shuffle(rng);
+    for value in values {
+        if *nodes == 0 {
+            return false;
         }
-        false
+        *nodes -= 1;
+        sudoku.assign(row, col, value);
+        if fill_randomly(sudoku, rng, nodes) {
+            return true;
+        }
+        sudoku.unassign(row, col);
     }
+    false
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Whether a grid that had one solution before the `removed` cells were emptied still has
+/// only that one; `original` holds the removed values. `None` if the search gave up after
+/// `node_limit` placements.
+fn still_unique(
+    sudoku: &Sudoku,
+    removed: &[(usize, usize)],
+    original: &Sudoku,
+    node_limit: Option<usize>,
+) -> Option<bool> {
+    // Any other solution differs from the original in a removed cell; look for one
+    // differing first at each removed cell in turn
+    let mut grid = sudoku.clone();
+    for &(row, col) in removed {
+        let value = original.cell(row, col).value().unwrap();
+        let mut differing = grid.clone();
+        differing.eliminate_candidate(row, col, value);
+
+        let mut links = DancingLinks::new(&differing);
+        links.set_node_limit(node_limit);
+        let mut found = false;
+        links.search(&mut |_| {
+            found = true;
+            false
+        });
+        if found {
+            return Some(false);
+        }
+        if links.stopped() {
+            return None;
+        }
+        grid.set(row, col, value).unwrap();
+    }
+    Some(true)
+}
+
+/// How hard a puzzle is, from the hardest technique it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Difficulty {
+    /// Singles only
     Easy,
+    /// Intersections, pairs and (finned) X-Wings
     Medium,
+    /// Triples, naked quads, Swordfish, wings and unique rectangles
     Hard,
+    /// Hidden quads, larger fish, BUG+1, chains, or more than the strategies can do
     Expert,
 }
 
+impl Difficulty {
+    /// The difficulty of a puzzle whose hardest technique has the given rating
+    pub fn from_rating(rating: f32) -> Self {
+        if rating <= 2.3 {
+            Difficulty::Easy
+        } else if rating <= 3.4 {
+            Difficulty::Medium
+        } else if rating <= 5.0 {
+            Difficulty::Hard
+        } else {
+            Difficulty::Expert
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = SudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            "expert" => Ok(Difficulty::Expert),
+            _ => Err(SudokuError::InvalidDifficulty(s.to_string())),
+        }
+    }
+}
+
+impl Default for SolverStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Default for SudokuSolver {
     fn default() -> Self {
         Self::new()
@@ -1,229 +1,624 @@
-use crate::sudoku::Sudoku;
+use crate::chains::{AlternatingInferenceChains, SimpleColoring, XChains};
+use crate::fish::{Fish, Jellyfish, Swordfish, XWing};
+use crate::killer::{CageCombinations, InniesAndOuties};
+use crate::sudoku::{mask_values, value_bit, Sudoku, Unit, UnitKind};
+use crate::uniqueness::{BugPlusOne, UniqueRectangles};
+use crate::wings::{WWing, XYWing, XYZWing};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
 
-pub trait SolvingStrategy {
-    fn apply(&self, sudoku: &mut Sudoku) -> bool;
-    fn name(&self) -> &'static str;
+/// A candidate removed from a cell's pencil marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Elimination {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
 }
 
-/// Naked Singles: If a cell has only one possible candidate, fill it
-pub struct NakedSingles;
+impl Elimination {
+    pub fn new(row: usize, col: usize, value: u8) -> Self {
+        Self { row, col, value }
+    }
+}
 
-impl SolvingStrategy for NakedSingles {
-    fn apply(&self, sudoku: &mut Sudoku) -> bool {
-        let mut progress = false;
-        
-        for row in 0..sudoku.size {
-            for col in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    let candidates = sudoku.get_candidates(row, col);
-                    if candidates.len() == 1 {
-                        let value = *candidates.iter().next().unwrap();
-                        sudoku.set(row, col, value).unwrap();
-                        progress = true;
+/// A value placed in a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Placement {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+}
+
+impl Placement {
+    pub fn new(row: usize, col: usize, value: u8) -> Self {
+        Self { row, col, value }
+    }
+}
+
+/// One deduction made by a strategy: what it places or removes, and the cells and units
+/// of the pattern that justify it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Step {
+    pub technique: String,
+    pub placements: Vec<Placement>,
+    pub eliminations: Vec<Elimination>,
+    pub cells: Vec<(usize, usize)>,
+    pub units: Vec<Unit>,
+    /// Walk-through of the reasoning for techniques that need one, such as chains
+    pub explanation: Option<String>,
+}
+
+impl Step {
+    /// A step placing `value` at (`row`, `col`)
+    pub fn placement(technique: &str, row: usize, col: usize, value: u8) -> Self {
+        Self {
+            technique: technique.to_string(),
+            placements: vec![Placement::new(row, col, value)],
+            eliminations: Vec::new(),
+            cells: vec![(row, col)],
+            units: Vec::new(),
+            explanation: None,
+        }
+    }
+
+    /// A step removing candidates
+    pub fn elimination(technique: &str, eliminations: Vec<Elimination>) -> Self {
+        Self {
+            technique: technique.to_string(),
+            placements: Vec::new(),
+            eliminations,
+            cells: Vec::new(),
+            units: Vec::new(),
+            explanation: None,
+        }
+    }
+
+    /// The values placed and candidates removed going from `before` to `after`, if any
+    pub fn between(technique: &str, before: &Sudoku, after: &Sudoku) -> Option<Self> {
+        let mut step = Self::elimination(technique, Vec::new());
+        for row in 0..before.size {
+            for col in 0..before.size {
+                if !before.cell(row, col).is_empty() {
+                    continue;
+                }
+                if let Some(value) = after.cell(row, col).value() {
+                    step.placements.push(Placement::new(row, col, value));
+                    step.cells.push((row, col));
+                    continue;
+                }
+                for value in 1..=before.size as u8 {
+                    if before.has_candidate(row, col, value)
+                        && !after.has_candidate(row, col, value)
+                    {
+                        step.eliminations.push(Elimination::new(row, col, value));
                     }
                 }
             }
         }
-        
+
+        if step.placements.is_empty() && step.eliminations.is_empty() {
+            None
+        } else {
+            Some(step)
+        }
+    }
+
+    pub fn with_cells(mut self, cells: Vec<(usize, usize)>) -> Self {
+        self.cells = cells;
+        self
+    }
+
+    pub fn with_units(mut self, units: Vec<Unit>) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn with_explanation(mut self, explanation: String) -> Self {
+        self.explanation = Some(explanation);
+        self
+    }
+
+    /// Apply the step to the grid, returning `true` if anything changed
+    pub fn apply(&self, sudoku: &mut Sudoku) -> bool {
+        let mut progress = apply_eliminations(sudoku, &self.eliminations);
+        for p in &self.placements {
+            if sudoku.cell(p.row, p.col).is_empty() && sudoku.set(p.row, p.col, p.value).is_ok() {
+                progress = true;
+            }
+        }
         progress
     }
+}
 
-    fn name(&self) -> &'static str {
-        "Naked Singles"
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.technique)?;
+        if !self.units.is_empty() {
+            let units: Vec<String> = self.units.iter().map(|unit| unit.to_string()).collect();
+            write!(f, " in {}", units.join(", "))?;
+        }
+
+        let changes: Vec<String> = self
+            .placements
+            .iter()
+            .map(|p| format!("r{}c{}={}", p.row + 1, p.col + 1, p.value))
+            .chain(
+                self.eliminations
+                    .iter()
+                    .map(|e| format!("r{}c{}<>{}", e.row + 1, e.col + 1, e.value)),
+            )
+            .collect();
+        write!(f, ": {}", changes.join(", "))
     }
 }
 
-/// Hidden Singles: If a value can only go in one cell in a unit (row, column, or box)
-pub struct HiddenSingles;
+/// A solving technique; clones of a solver share their strategies, across threads too
+pub trait SolvingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
 
-impl SolvingStrategy for HiddenSingles {
+    /// How hard the technique is for a person, on the Sudoku Explainer scale (1.0 to 11.0).
+    /// Strategies that do not give one rate 11.0, so puzzles needing them grade as hardest.
+    fn rating(&self) -> f32 {
+        11.0
+    }
+
+    /// Every deduction the strategy can make on the current grid, without modifying it.
+    /// By default this runs `apply` on a copy and reports what changed as a single step, so
+    /// a strategy must implement at least one of `find_steps` and `apply`.
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut after = sudoku.clone();
+        if !self.apply(&mut after) {
+            return Vec::new();
+        }
+        Step::between(self.name(), sudoku, &after)
+            .into_iter()
+            .collect()
+    }
+
+    /// The first deduction the strategy can make on the current grid
+    fn find_step(&self, sudoku: &Sudoku) -> Option<Step> {
+        self.find_steps(sudoku).into_iter().next()
+    }
+
+    /// Apply every deduction found on the current grid, returning `true` if it changed
     fn apply(&self, sudoku: &mut Sudoku) -> bool {
         let mut progress = false;
-        
-        // Check rows
-        for row in 0..sudoku.size {
-            progress |= self.apply_to_row(sudoku, row);
-        }
-        
-        // Check columns
-        for col in 0..sudoku.size {
-            progress |= self.apply_to_col(sudoku, col);
-        }
-        
-        // Check boxes
-        for box_row in 0..sudoku.box_size {
-            for box_col in 0..sudoku.box_size {
-                progress |= self.apply_to_box(sudoku, box_row, box_col);
-            }
+        for step in self.find_steps(sudoku) {
+            progress |= step.apply(sudoku);
         }
-        
         progress
     }
 
-    fn name(&self) -> &'static str {
-        "Hidden Singles"
+    /// Every candidate the strategy can remove from the current grid
+    fn find_eliminations(&self, sudoku: &Sudoku) -> Vec<Elimination> {
+        self.find_steps(sudoku)
+            .into_iter()
+            .flat_map(|step| step.eliminations)
+            .collect()
+    }
+
+    /// Whether the strategy is only sound for puzzles with a unique solution
+    fn requires_uniqueness(&self) -> bool {
+        false
+    }
+
+    /// Whether the strategy reasons about the sums of Killer cages
+    fn uses_cages(&self) -> bool {
+        false
     }
 }
 
-impl HiddenSingles {
-    fn apply_to_row(&self, sudoku: &mut Sudoku, row: usize) -> bool {
-        let mut progress = false;
-        
-        for value in 1..=sudoku.size as u8 {
-            let mut possible_cells = Vec::new();
-            
+/// Remove the given candidates from the grid, returning `true` if any were still present
+pub fn apply_eliminations(sudoku: &mut Sudoku, eliminations: &[Elimination]) -> bool {
+    let mut progress = false;
+    for e in eliminations {
+        progress |= sudoku.eliminate_candidate(e.row, e.col, e.value);
+    }
+    progress
+}
+
+/// Call `f` with every `k`-element combination of `0..n`, in lexicographic order
+pub(crate) fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(&[usize])) {
+    if k == 0 || k > n {
+        return;
+    }
+
+    let mut indices: Vec<usize> = (0..k).collect();
+    loop {
+        f(&indices);
+
+        // Advance the rightmost index that still has room to move
+        let mut i = k;
+        while i > 0 && indices[i - 1] == n - k + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return;
+        }
+        indices[i - 1] += 1;
+        for j in i..k {
+            indices[j] = indices[j - 1] + 1;
+        }
+    }
+}
+
+/// Deduplicate eliminations while preserving the order they were found in
+pub(crate) fn dedup_eliminations(eliminations: &mut Vec<Elimination>) {
+    let mut seen = HashSet::new();
+    eliminations.retain(|e| seen.insert(*e));
+}
+
+/// Add a step unless it only repeats eliminations made by earlier steps
+pub(crate) fn push_step(steps: &mut Vec<Step>, mut step: Step) {
+    let mut seen: HashSet<Elimination> = steps
+        .iter()
+        .flat_map(|step| step.eliminations.iter().copied())
+        .collect();
+    step.eliminations.retain(|e| seen.insert(*e));
+    if !step.eliminations.is_empty() || !step.placements.is_empty() {
+        steps.push(step);
+    }
+}
+
+/// Naked Singles: If a cell has only one possible candidate, fill it
+pub struct NakedSingles;
+
+impl SolvingStrategy for NakedSingles {
+    fn name(&self) -> &'static str {
+        "Naked Singles"
+    }
+
+    fn rating(&self) -> f32 {
+        2.3
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+
+        for row in 0..sudoku.size {
             for col in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    let candidates = sudoku.get_candidates(row, col);
-                    if candidates.contains(&value) {
-                        possible_cells.push(col);
-                    }
+                if sudoku.cell(row, col).is_empty() && sudoku.candidate_count(row, col) == 1 {
+                    let value = mask_values(sudoku.candidate_mask(row, col)).next().unwrap();
+                    steps.push(Step::placement(self.name(), row, col, value));
                 }
             }
-            
-            if possible_cells.len() == 1 {
-                let col = possible_cells[0];
-                sudoku.set(row, col, value).unwrap();
-                progress = true;
-            }
         }
-        
-        progress
+
+        steps
     }
-    
-    fn apply_to_col(&self, sudoku: &mut Sudoku, col: usize) -> bool {
-        let mut progress = false;
-        
-        for value in 1..=sudoku.size as u8 {
-            let mut possible_cells = Vec::new();
-            
-            for row in 0..sudoku.size {
-                if sudoku.grid[row][col].is_empty() {
-                    let candidates = sudoku.get_candidates(row, col);
-                    if candidates.contains(&value) {
-                        possible_cells.push(row);
-                    }
+}
+
+/// Hidden Singles: If a value can only go in one cell in a unit (row, column, or box)
+pub struct HiddenSingles;
+
+impl SolvingStrategy for HiddenSingles {
+    fn name(&self) -> &'static str {
+        "Hidden Singles"
+    }
+
+    fn rating(&self) -> f32 {
+        1.5
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps: Vec<Step> = Vec::new();
+
+        for unit in sudoku.units() {
+            let cells = sudoku.unit_cells(unit);
+            for value in 1..=sudoku.size as u8 {
+                let mut possible_cells = cells
+                    .iter()
+                    .filter(|&&(row, col)| sudoku.has_candidate(row, col, value));
+                let (row, col) = match (possible_cells.next(), possible_cells.next()) {
+                    (Some(&cell), None) => cell,
+                    _ => continue,
+                };
+
+                // The same cell can be a hidden single in more than one unit
+                if steps.iter().any(|step| step.cells == [(row, col)]) {
+                    continue;
                 }
-            }
-            
-            if possible_cells.len() == 1 {
-                let row = possible_cells[0];
-                sudoku.set(row, col, value).unwrap();
-                progress = true;
+                steps.push(Step::placement(self.name(), row, col, value).with_units(vec![unit]));
             }
         }
-        
-        progress
+
+        steps
     }
-    
-    fn apply_to_box(&self, sudoku: &mut Sudoku, box_row: usize, box_col: usize) -> bool {
-        let mut progress = false;
-        
-        for value in 1..=sudoku.size as u8 {
-            let mut possible_cells = Vec::new();
-            
-            for row in box_row * sudoku.box_size..(box_row + 1) * sudoku.box_size {
-                for col in box_col * sudoku.box_size..(box_col + 1) * sudoku.box_size {
-                    if sudoku.grid[row][col].is_empty() {
-                        let candidates = sudoku.get_candidates(row, col);
-                        if candidates.contains(&value) {
-                            possible_cells.push((row, col));
-                        }
-                    }
+}
+
+/// Naked subsets: if `n` cells in a unit share exactly `n` candidates between them,
+/// those candidates can be removed from every other cell of the unit
+fn naked_subset_steps(sudoku: &Sudoku, n: usize, technique: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    for unit in sudoku.units() {
+        let empty: Vec<(usize, usize)> = sudoku
+            .unit_cells(unit)
+            .into_iter()
+            .filter(|&(row, col)| sudoku.cell(row, col).is_empty())
+            .collect();
+        let subset_cells: Vec<(usize, usize)> = empty
+            .iter()
+            .copied()
+            .filter(|&(row, col)| (2..=n).contains(&sudoku.candidate_count(row, col)))
+            .collect();
+
+        if empty.len() <= n {
+            continue;
+        }
+
+        for_each_combination(subset_cells.len(), n, |combo| {
+            let cells: Vec<(usize, usize)> = combo.iter().map(|&i| subset_cells[i]).collect();
+            let union = cells.iter().fold(0, |mask, &(row, col)| {
+                mask | sudoku.candidate_mask(row, col)
+            });
+            if union.count_ones() as usize != n {
+                return;
+            }
+
+            let mut eliminations = Vec::new();
+            for &(row, col) in &empty {
+                if cells.contains(&(row, col)) {
+                    continue;
+                }
+                for value in mask_values(sudoku.candidate_mask(row, col) & union) {
+                    eliminations.push(Elimination::new(row, col, value));
                 }
             }
-            
-            if possible_cells.len() == 1 {
-                let (row, col) = possible_cells[0];
-                sudoku.set(row, col, value).unwrap();
-                progress = true;
+            let step = Step::elimination(technique, eliminations)
+                .with_cells(cells)
+                .with_units(vec![unit]);
+            push_step(&mut steps, step);
+        });
+    }
+
+    steps
+}
+
+/// Hidden subsets: if `n` values are confined to the same `n` cells of a unit,
+/// every other candidate can be removed from those cells
+fn hidden_subset_steps(sudoku: &Sudoku, n: usize, technique: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    for unit in sudoku.units() {
+        let cells = sudoku.unit_cells(unit);
+
+        // Positions (as a bitmask over the unit's cells) where each value can still go
+        let mut positions: Vec<(u8, u32)> = Vec::new();
+        for value in 1..=sudoku.size as u8 {
+            let mask = cells
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(row, col))| sudoku.has_candidate(row, col, value))
+                .fold(0u32, |mask, (i, _)| mask | (1 << i));
+            if (1..=n as u32).contains(&mask.count_ones()) {
+                positions.push((value, mask));
             }
         }
-        
-        progress
+
+        for_each_combination(positions.len(), n, |combo| {
+            let union = combo.iter().fold(0u32, |mask, &i| mask | positions[i].1);
+            if union.count_ones() as usize != n {
+                return;
+            }
+
+            let keep = combo
+                .iter()
+                .fold(0u32, |mask, &i| mask | value_bit(positions[i].0));
+            let mut eliminations = Vec::new();
+            let mut subset = Vec::new();
+            for (i, &(row, col)) in cells.iter().enumerate() {
+                if union & (1 << i) == 0 {
+                    continue;
+                }
+                subset.push((row, col));
+                for value in mask_values(sudoku.candidate_mask(row, col) & !keep) {
+                    eliminations.push(Elimination::new(row, col, value));
+                }
+            }
+            let step = Step::elimination(technique, eliminations)
+                .with_cells(subset)
+                .with_units(vec![unit]);
+            push_step(&mut steps, step);
+        });
     }
+
+    steps
 }
 
-/// Naked Pairs: If two cells in a unit have the same two candidates, eliminate those from other cells
-pub struct NakedPairs;
+macro_rules! subset_strategy {
+    ($(#[$doc:meta])* $name:ident, $label:expr, $rating:expr, $finder:ident, $n:expr) => {
+        $(#[$doc])*
+        pub struct $name;
 
-impl SolvingStrategy for NakedPairs {
-    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
-        // This is a more complex strategy that would modify candidate lists
-        // For now, we'll implement a simplified version
-        false
-    }
+        impl SolvingStrategy for $name {
+            fn name(&self) -> &'static str {
+                $label
+            }
 
-    fn name(&self) -> &'static str {
-        "Naked Pairs"
+            fn rating(&self) -> f32 {
+                $rating
+            }
+
+            fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+                $finder(sudoku, $n, $label)
+            }
+        }
+    };
+}
+
+subset_strategy!(
+    /// Naked Pairs: If two cells in a unit have the same two candidates, eliminate those from other cells
+    NakedPairs,
+    "Naked Pairs",
+    3.0,
+    naked_subset_steps,
+    2
+);
+subset_strategy!(
+    /// Naked Triples: Three cells in a unit whose candidates are limited to the same three values
+    NakedTriples,
+    "Naked Triples",
+    3.6,
+    naked_subset_steps,
+    3
+);
+subset_strategy!(
+    /// Naked Quads: Four cells in a unit whose candidates are limited to the same four values
+    NakedQuads,
+    "Naked Quads",
+    5.0,
+    naked_subset_steps,
+    4
+);
+subset_strategy!(
+    /// Hidden Pairs: Two values that can only go in the same two cells of a unit
+    HiddenPairs,
+    "Hidden Pairs",
+    3.4,
+    hidden_subset_steps,
+    2
+);
+subset_strategy!(
+    /// Hidden Triples: Three values that can only go in the same three cells of a unit
+    HiddenTriples,
+    "Hidden Triples",
+    4.0,
+    hidden_subset_steps,
+    3
+);
+subset_strategy!(
+    /// Hidden Quads: Four values that can only go in the same four cells of a unit
+    HiddenQuads,
+    "Hidden Quads",
+    5.4,
+    hidden_subset_steps,
+    4
+);
+
+/// Locked candidates: if every candidate for a value in a `base` unit lies inside a single
+/// unit of another kind, the value can be removed from the rest of that unit
+fn intersection_steps(sudoku: &Sudoku, base_kinds: &[UnitKind], technique: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    for base in sudoku.units() {
+        if !base_kinds.contains(&base.kind) {
+            continue;
+        }
+        let base_cells = sudoku.unit_cells(base);
+
+        for value in 1..=sudoku.size as u8 {
+            let cells: Vec<(usize, usize)> = base_cells
+                .iter()
+                .copied()
+                .filter(|&(row, col)| sudoku.has_candidate(row, col, value))
+                .collect();
+            if cells.len() < 2 {
+                continue;
+            }
+
+            let (first_row, first_col) = cells[0];
+            for cover in sudoku.cell_units(first_row, first_col) {
+                if cover.kind == base.kind {
+                    continue;
+                }
+                let cover_cells = sudoku.unit_cells(cover);
+                if !cells.iter().all(|cell| cover_cells.contains(cell)) {
+                    continue;
+                }
+
+                let mut eliminations = Vec::new();
+                for &(row, col) in &cover_cells {
+                    if !base_cells.contains(&(row, col)) && sudoku.has_candidate(row, col, value) {
+                        eliminations.push(Elimination::new(row, col, value));
+                    }
+                }
+                let step = Step::elimination(technique, eliminations)
+                    .with_cells(cells.clone())
+                    .with_units(vec![base, cover]);
+                push_step(&mut steps, step);
+            }
+        }
     }
+
+    steps
 }
 
 /// Pointing Pairs/Triples: If all candidates for a value in a box are in the same row/column
 pub struct PointingPairs;
 
 impl SolvingStrategy for PointingPairs {
-    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
-        // This is a candidate elimination strategy
-        // For simplicity, we'll implement basic logic
-        false
-    }
-
     fn name(&self) -> &'static str {
         "Pointing Pairs"
     }
-}
-
-/// Box/Line Reduction: If all candidates for a value in a row/column are in the same box
-pub struct BoxLineReduction;
 
-impl SolvingStrategy for BoxLineReduction {
-    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
-        // This is a candidate elimination strategy
-        false
+    fn rating(&self) -> f32 {
+        2.6
     }
 
-    fn name(&self) -> &'static str {
-        "Box/Line Reduction"
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        intersection_steps(sudoku, &[UnitKind::Box], self.name())
     }
 }
 
-/// X-Wing: Advanced pattern recognition strategy
-pub struct XWing;
-
-impl SolvingStrategy for XWing {
-    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
-        // Advanced strategy - simplified for now
-        false
-    }
+/// Box/Line Reduction: If all candidates for a value in a row/column are in the same box
+pub struct BoxLineReduction;
 
+impl SolvingStrategy for BoxLineReduction {
     fn name(&self) -> &'static str {
-        "X-Wing"
+        "Box/Line Reduction"
     }
-}
-
-/// Swordfish: Even more advanced pattern recognition
-pub struct Swordfish;
 
-impl SolvingStrategy for Swordfish {
-    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
-        // Very advanced strategy - simplified for now
-        false
+    fn rating(&self) -> f32 {
+        2.8
     }
 
-    fn name(&self) -> &'static str {
-        "Swordfish"
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        intersection_steps(sudoku, &[UnitKind::Row, UnitKind::Column], self.name())
     }
 }
 
 pub fn get_all_strategies() -> Vec<Box<dyn SolvingStrategy>> {
-    vec![
+    let mut strategies: Vec<Box<dyn SolvingStrategy>> = vec![
         Box::new(NakedSingles),
         Box::new(HiddenSingles),
+        // Only find anything on Killer grids
+        Box::new(CageCombinations),
         Box::new(NakedPairs),
+        Box::new(HiddenPairs),
         Box::new(PointingPairs),
         Box::new(BoxLineReduction),
+        Box::new(InniesAndOuties),
+        Box::new(NakedTriples),
+        Box::new(HiddenTriples),
+        Box::new(NakedQuads),
+        Box::new(HiddenQuads),
         Box::new(XWing),
+        Box::new(Fish::finned(2)),
         Box::new(Swordfish),
-    ]
+        Box::new(SimpleColoring),
+        // Only used when the solver assumes a unique solution
+        Box::new(UniqueRectangles),
+        Box::new(XYWing),
+        Box::new(XYZWing),
+        Box::new(BugPlusOne),
+        Box::new(WWing),
+        Box::new(Fish::finned(3)),
+        Box::new(Jellyfish),
+        Box::new(Fish::finned(4)),
+        Box::new(XChains),
+    ];
+    // Larger fish only apply to grids of at least twice their order, such as 16x16 and 25x25,
+    // so every order up to half the largest grid is registered and each grid uses its own
+    let max_order = (u32::BITS as usize - 1) / 2;
+    for order in 5..=max_order {
+        strategies.push(Box::new(Fish::new(order)));
+    }
+    strategies.push(Box::new(AlternatingInferenceChains::new()));
+    strategies
 }
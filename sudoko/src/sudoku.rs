@@ -1,7 +1,11 @@
+use crate::error::SudokuError;
+use crate::killer::{combination_mask, Cage};
+use crate::layout::Layout;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -28,202 +32,649 @@ impl Cell {
     }
 }
 
+/// The kind of house a unit is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnitKind {
+    Row,
+    Column,
+    Box,
+    /// A main diagonal, in X-Sudoku
+    Diagonal,
+    /// A window between the boxes, in Windoku
+    Window,
+    /// A Killer cage; not a house, as it need not hold every value
+    Cage,
+    /// Any other house added beyond the rows, columns and boxes
+    Extra,
+}
+
+/// A house of the grid: a row, column, box or extra house. Standard boxes are numbered
+/// row-major from the top left; other houses are numbered in the order of their kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Unit {
+    pub kind: UnitKind,
+    pub index: usize,
+}
+
+impl Unit {
+    pub fn new(kind: UnitKind, index: usize) -> Self {
+        Self { kind, index }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            UnitKind::Row => "row",
+            UnitKind::Column => "column",
+            UnitKind::Box => "box",
+            UnitKind::Diagonal => "diagonal",
+            UnitKind::Window => "window",
+            UnitKind::Cage => "cage",
+            UnitKind::Extra => "house",
+        };
+        write!(f, "{} {}", kind, self.index + 1)
+    }
+}
+
+/// A value placed more than once in a unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Conflict {
+    pub unit: Unit,
+    pub value: u8,
+    /// The cells holding the value, in unit order
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<String> = self
+            .cells
+            .iter()
+            .map(|&(row, col)| format!("r{}c{}", row + 1, col + 1))
+            .collect();
+        write!(
+            f,
+            "{} appears {} times in {}: {}",
+            self.value,
+            self.cells.len(),
+            self.unit,
+            cells.join(", ")
+        )
+    }
+}
+
+/// A value in two neighbouring cells that must differ without sharing a house, such as
+/// cells a knight's move apart in anti-knight Sudoku
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clash {
+    pub value: u8,
+    /// The two cells, in row-major order
+    pub cells: [(usize, usize); 2],
+}
+
+impl fmt::Display for Clash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [(r1, c1), (r2, c2)] = self.cells;
+        write!(
+            f,
+            "{} appears in neighbouring cells r{}c{} and r{}c{}",
+            self.value,
+            r1 + 1,
+            c1 + 1,
+            r2 + 1,
+            c2 + 1
+        )
+    }
+}
+
+/// A Killer cage whose values add up to more than its sum, or to anything else once full
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CageError {
+    pub cage: Unit,
+    /// What the placed values add up to
+    pub total: u32,
+    pub sum: u32,
+    /// The cells of the cage, in row-major order
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for CageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} adds up to {}, not {}",
+            self.cage, self.total, self.sum
+        )
+    }
+}
+
+/// Everything wrong with a grid: repeated values and empty cells left without candidates
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationReport {
+    /// Every repeated value, ordered by rows, then columns, then boxes, then extra houses,
+    /// then cages
+    pub conflicts: Vec<Conflict>,
+    /// Every value repeated in neighbouring cells that share no house, in row-major order
+    pub clashes: Vec<Clash>,
+    /// Every cage adding up to the wrong sum, in cage order
+    pub cage_errors: Vec<CageError>,
+    /// Empty cells whose pencil marks are all eliminated, in row-major order
+    pub dead_cells: Vec<(usize, usize)>,
+    pub complete: bool,
+}
+
+impl ValidationReport {
+    /// No value is repeated in any unit or in neighbouring cells, and no cage is over its sum
+    pub fn is_valid(&self) -> bool {
+        self.conflicts.is_empty() && self.clashes.is_empty() && self.cage_errors.is_empty()
+    }
+
+    /// Complete and valid
+    pub fn is_solved(&self) -> bool {
+        self.complete && self.is_valid()
+    }
+
+    pub fn valid_rows(&self) -> bool {
+        self.valid_units(UnitKind::Row)
+    }
+
+    pub fn valid_cols(&self) -> bool {
+        self.valid_units(UnitKind::Column)
+    }
+
+    pub fn valid_boxes(&self) -> bool {
+        self.valid_units(UnitKind::Box)
+    }
+
+    fn valid_units(&self, kind: UnitKind) -> bool {
+        self.conflicts
+            .iter()
+            .all(|conflict| conflict.unit.kind != kind)
+    }
+
+    /// Cells involved in any conflict or wrong cage, in row-major order
+    pub fn conflicting_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells: Vec<(usize, usize)> = self
+            .conflicts
+            .iter()
+            .flat_map(|conflict| conflict.cells.iter().copied())
+            .chain(self.clashes.iter().flat_map(|clash| clash.cells))
+            .chain(
+                self.cage_errors
+                    .iter()
+                    .flat_map(|error| error.cells.iter().copied()),
+            )
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+/// Bit used for `value` in a candidate mask (bit `value - 1`)
+pub fn value_bit(value: u8) -> u32 {
+    1 << (value - 1)
+}
+
+/// Iterate over the values set in a candidate mask, in ascending order
+pub fn mask_values(mask: u32) -> impl Iterator<Item = u8> {
+    (0..u32::BITS as u8)
+        .filter(move |bit| mask & (1 << bit) != 0)
+        .map(|bit| bit + 1)
+}
+
+pub(crate) fn full_mask(size: usize) -> u32 {
+    (1u32 << size) - 1
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Sudoku {
-    pub grid: Vec<Vec<Cell>>,
+    /// Cells in row-major order
+    cells: Vec<Cell>,
     pub size: usize,
-    pub box_size: usize,
+    /// Rows and columns of each box, or of the usual box for the size when the boxes are
+    /// irregular
+    pub box_rows: usize,
+    pub box_cols: usize,
+    /// The houses, shared between copies of the grid
+    layout: Arc<Layout>,
+    /// Pencil marks: remaining candidates per cell, row-major, zero for filled cells
+    candidates: Vec<u32>,
+    /// Values placed in each unit, in layout order
+    unit_masks: Vec<u32>,
+    /// How often each value is placed in each unit, in layout order, so duplicates can be
+    /// tracked as values come and go
+    counts: Vec<u8>,
+    /// Number of (unit, value) pairs placed more than once
+    duplicates: usize,
+    /// Number of pairs of neighbouring cells holding the same value
+    clashes: usize,
+    /// What the placed values of each cage add up to, in layout order
+    cage_totals: Vec<u32>,
+    /// Number of placed values in each cage
+    cage_filled: Vec<usize>,
+    /// Number of cages over their sum, or full and under it
+    broken_cages: usize,
+    filled: usize,
 }
 
 impl Sudoku {
     pub fn new(size: usize) -> Self {
-        let box_size = (size as f64).sqrt() as usize;
-        if box_size * box_size != size {
-            panic!("Invalid Sudoku size: {} is not a perfect square", size);
+        match Layout::standard(size) {
+            Ok(layout) => Self::with_layout(layout),
+            Err(e) => panic!("{}", e),
         }
+    }
 
+    /// An empty grid whose houses are those of `layout`
+    pub fn with_layout(layout: Layout) -> Self {
+        let size = layout.size();
+        let units = layout.units().len();
+        let cages = layout.cages().len();
+        let (box_rows, box_cols) = layout
+            .box_shape()
+            .or_else(|| Layout::box_shape_for(size))
+            .unwrap_or((1, size));
         Self {
-            grid: vec![vec![Cell::Empty; size]; size],
+            cells: vec![Cell::Empty; size * size],
             size,
-            box_size,
+            box_rows,
+            box_cols,
+            layout: Arc::new(layout),
+            candidates: vec![full_mask(size); size * size],
+            unit_masks: vec![0; units],
+            counts: vec![0; units * size],
+            duplicates: 0,
+            clashes: 0,
+            cage_totals: vec![0; cages],
+            cage_filled: vec![0; cages],
+            broken_cages: 0,
+            filled: 0,
         }
     }
 
-    pub fn from_string(s: &str, size: usize) -> Result<Self, String> {
-        let mut sudoku = Self::new(size);
+    pub fn from_string(s: &str, size: usize) -> Result<Self, SudokuError> {
+        Self::from_string_with_layout(s, Layout::standard(size)?)
+    }
+
+    /// Read a puzzle as `from_string` does, on a grid with the houses of `layout`
+    pub fn from_string_with_layout(s: &str, layout: Layout) -> Result<Self, SudokuError> {
+        let size = layout.size();
+        let mut sudoku = Self::with_layout(layout);
         let chars: Vec<char> = s.chars().collect();
 
         if chars.len() != size * size {
-            return Err(format!(
-                "Invalid input length: expected {}, got {}",
-                size * size,
-                chars.len()
-            ));
+            return Err(SudokuError::InvalidLength {
+                expected: size * size,
+                found: chars.len(),
+            });
         }
 
         for (i, &ch) in chars.iter().enumerate() {
             let row = i / size;
             let col = i % size;
 
-            match ch {
-                '0' | '.' | ' ' => sudoku.grid[row][col] = Cell::Empty,
-                _ => {
-                    if let Some(digit) = ch.to_digit(10) {
-                        let value = digit as u8;
-                        if value >= 1 && value <= size as u8 {
-                            sudoku.grid[row][col] = Cell::Given(value);
-                        } else {
-                            return Err(format!(
-                                "Invalid digit {} at position ({}, {})",
-                                value, row, col
-                            ));
-                        }
-                    } else if ch.is_ascii_uppercase() {
-                        // Support hexadecimal for larger Sudokus
-                        let value = ch as u8 - b'A' + 10;
-                        if value >= 10 && value <= size as u8 {
-                            sudoku.grid[row][col] = Cell::Given(value);
-                        } else {
-                            return Err(format!(
-                                "Invalid character {} at position ({}, {})",
-                                ch, row, col
-                            ));
-                        }
-                    } else {
-                        return Err(format!(
-                            "Invalid character {} at position ({}, {})",
-                            ch, row, col
-                        ));
-                    }
-                }
+            let value = match ch {
+                '0' | '.' | ' ' => continue,
+                '1'..='9' => ch as u8 - b'0',
+                // Support hexadecimal for larger Sudokus
+                'A'..='Z' => ch as u8 - b'A' + 10,
+                _ => 0,
+            };
+            if value == 0 || value as usize > size {
+                return Err(SudokuError::InvalidCharacter {
+                    row,
+                    col,
+                    character: ch,
+                });
             }
+            sudoku.put(row, col, Cell::Given(value));
         }
 
+        sudoku.reset_candidates();
         Ok(sudoku)
     }
 
+    /// Read a Jigsaw puzzle: the digits as `from_string` reads them, and the regions as
+    /// `Layout::from_region_map` reads them
+    pub fn from_jigsaw(s: &str, regions: &str, size: usize) -> Result<Self, SudokuError> {
+        Self::from_string_with_layout(s, Layout::from_region_map(regions, size)?)
+    }
+
+    /// Read a Killer puzzle: the digits as `from_string` reads them, often all empty, and
+    /// the cages as `Cage::parse_list` reads them
+    pub fn from_killer(s: &str, cages: &str, size: usize) -> Result<Self, SudokuError> {
+        let mut layout = Layout::standard(size)?;
+        for cage in Cage::parse_list(cages)? {
+            layout.add_cage(cage)?;
+        }
+        Self::from_string_with_layout(s, layout)
+    }
+
+    /// The grid on one line, in the format `from_string` reads, with `0` for empty cells
+    pub fn to_line(&self) -> String {
+        self.cells
+            .iter()
+            .map(|cell| match cell.value() {
+                None => '0',
+                Some(v) if v <= 9 => (b'0' + v) as char,
+                Some(v) => (b'A' + v - 10) as char,
+            })
+            .collect()
+    }
+
     pub fn get(&self, row: usize, col: usize) -> Option<Cell> {
         if row < self.size && col < self.size {
-            Some(self.grid[row][col])
+            Some(self.cell(row, col))
         } else {
             None
         }
     }
 
-    pub fn set(&mut self, row: usize, col: usize, value: u8) -> Result<(), String> {
+    /// The cell at (`row`, `col`); panics if the position is outside the grid
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        self.cells[row * self.size + col]
+    }
+
+    /// The grid as rows of cells, copied out of the flat storage
+    #[deprecated(note = "the grid is stored flat; use `cell(row, col)` or `get(row, col)`")]
+    pub fn grid(&self) -> Vec<Vec<Cell>> {
+        self.cells
+            .chunks(self.size)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// The side of a square box
+    #[deprecated(note = "boxes can be rectangular; use `box_rows` and `box_cols`")]
+    pub fn box_size(&self) -> usize {
+        self.box_rows
+    }
+
+    /// Number of empty cells in the grid
+    pub fn empty_cells(&self) -> usize {
+        self.size * self.size - self.filled
+    }
+
+    /// Number of given cells
+    pub fn clue_count(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_given()).count()
+    }
+
+    /// Turn every placed value into a given, as the clues of a new puzzle
+    pub(crate) fn mark_givens(&mut self) {
+        for cell in &mut self.cells {
+            if let Cell::Filled(value) = *cell {
+                *cell = Cell::Given(value);
+            }
+        }
+    }
+
+    /// The houses of the grid
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Write a cell, keeping the unit masks, duplicate counts and cage totals up to date
+    /// (but not the pencil marks)
+    fn put(&mut self, row: usize, col: usize, cell: Cell) {
+        let size = self.size;
+        let index = row * size + col;
+        let cage = self.layout.cage_index(row, col);
+        if let Some(cage) = cage {
+            if self.cage_is_broken(cage) {
+                self.broken_cages -= 1;
+            }
+        }
+
+        if let Some(old) = self.cells[index].value() {
+            self.filled -= 1;
+            for &unit in self.layout.units_of(index) {
+                let count = &mut self.counts[unit * size + old as usize - 1];
+                *count -= 1;
+                if *count > 0 {
+                    self.duplicates -= 1;
+                } else {
+                    self.unit_masks[unit] &= !value_bit(old);
+                }
+            }
+            for &other in self.layout.neighbours_of(index) {
+                if self.cells[other].value() == Some(old) {
+                    self.clashes -= 1;
+                }
+            }
+            if let Some(cage) = cage {
+                self.cage_totals[cage] -= u32::from(old);
+                self.cage_filled[cage] -= 1;
+            }
+        }
+
+        if let Some(new) = cell.value() {
+            self.filled += 1;
+            for &unit in self.layout.units_of(index) {
+                let count = &mut self.counts[unit * size + new as usize - 1];
+                *count += 1;
+                if *count > 1 {
+                    self.duplicates += 1;
+                }
+                self.unit_masks[unit] |= value_bit(new);
+            }
+            for &other in self.layout.neighbours_of(index) {
+                if self.cells[other].value() == Some(new) {
+                    self.clashes += 1;
+                }
+            }
+            if let Some(cage) = cage {
+                self.cage_totals[cage] += u32::from(new);
+                self.cage_filled[cage] += 1;
+            }
+        }
+
+        self.cells[index] = cell;
+        if let Some(cage) = cage {
+            if self.cage_is_broken(cage) {
+                self.broken_cages += 1;
+            }
+        }
+    }
+
+    /// Whether the values placed in a cage add up to more than its sum, or it is full and
+    /// they add up to less
+    fn cage_is_broken(&self, cage: usize) -> bool {
+        let (sum, cells) = {
+            let cage = &self.layout.cages()[cage];
+            (cage.sum, cage.cells.len())
+        };
+        let total = self.cage_totals[cage];
+        total > sum || (self.cage_filled[cage] == cells && total != sum)
+    }
+
+    /// Values an empty cell can take with the rest of its cage still able to make the
+    /// cage's sum from different values; every value outside cages
+    pub(crate) fn cage_mask(&self, row: usize, col: usize) -> u32 {
+        let cage = match self.layout.cage_index(row, col) {
+            Some(cage) => cage,
+            None => return full_mask(self.size),
+        };
+        let cells = &self.layout.cages()[cage].cells;
+        let placed = cells
+            .iter()
+            .filter_map(|&(row, col)| self.cell(row, col).value())
+            .fold(0, |mask, value| mask | value_bit(value));
+        match self.layout.cages()[cage]
+            .sum
+            .checked_sub(self.cage_totals[cage])
+        {
+            Some(left) => combination_mask(
+                full_mask(self.size) & !placed,
+                cells.len() - self.cage_filled[cage],
+                left,
+            ),
+            None => 0,
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: u8) -> Result<(), SudokuError> {
         if row >= self.size || col >= self.size {
-            return Err("Invalid position".to_string());
+            return Err(SudokuError::InvalidPosition { row, col });
         }
 
+        let previous = self.cell(row, col);
+
         if value == 0 {
-            self.grid[row][col] = Cell::Empty;
+            self.put(row, col, Cell::Empty);
         } else if value > self.size as u8 {
-            return Err(format!(
-                "Value {} is too large for {}x{} Sudoku",
-                value, self.size, self.size
-            ));
+            return Err(SudokuError::ValueOutOfRange {
+                value,
+                size: self.size,
+            });
         } else {
-            self.grid[row][col] = Cell::Filled(value);
+            self.put(row, col, Cell::Filled(value));
+        }
+
+        if !previous.is_empty() {
+            // Eliminations made while the old value was placed may no longer hold
+            self.reset_candidates();
+        } else if value != 0 {
+            self.place_candidate(row, col, value);
         }
 
         Ok(())
     }
 
-    pub fn is_valid(&self) -> bool {
-        self.is_valid_rows() && self.is_valid_cols() && self.is_valid_boxes()
+    /// Bitmask of the remaining candidates for a cell (see `value_bit`)
+    pub fn candidate_mask(&self, row: usize, col: usize) -> u32 {
+        self.candidates[row * self.size + col]
     }
 
-    pub fn is_valid_rows(&self) -> bool {
-        for row in 0..self.size {
-            let mut seen = HashSet::new();
-            for col in 0..self.size {
-                if let Some(value) = self.grid[row][col].value() {
-                    if !seen.insert(value) {
-                        return false;
-                    }
-                }
-            }
-        }
-        true
+    /// Check whether `value` is still a pencil mark of the cell
+    pub fn has_candidate(&self, row: usize, col: usize, value: u8) -> bool {
+        value >= 1
+            && value <= self.size as u8
+            && self.candidate_mask(row, col) & value_bit(value) != 0
     }
 
-    pub fn is_valid_cols(&self) -> bool {
-        for col in 0..self.size {
-            let mut seen = HashSet::new();
-            for row in 0..self.size {
-                if let Some(value) = self.grid[row][col].value() {
-                    if !seen.insert(value) {
-                        return false;
-                    }
-                }
-            }
+    /// Number of remaining candidates for a cell
+    pub fn candidate_count(&self, row: usize, col: usize) -> usize {
+        self.candidate_mask(row, col).count_ones() as usize
+    }
+
+    /// Remaining candidates for a cell in ascending order
+    pub fn candidate_values(&self, row: usize, col: usize) -> Vec<u8> {
+        mask_values(self.candidate_mask(row, col)).collect()
+    }
+
+    /// Remove a candidate from a cell's pencil marks, returning `true` if it was present
+    pub fn eliminate_candidate(&mut self, row: usize, col: usize, value: u8) -> bool {
+        if !self.has_candidate(row, col, value) {
+            return false;
         }
+        self.candidates[row * self.size + col] &= !value_bit(value);
         true
     }
 
-    pub fn is_valid_boxes(&self) -> bool {
-        for box_row in 0..self.box_size {
-            for box_col in 0..self.box_size {
-                let mut seen = HashSet::new();
-                for row in box_row * self.box_size..(box_row + 1) * self.box_size {
-                    for col in box_col * self.box_size..(box_col + 1) * self.box_size {
-                        if let Some(value) = self.grid[row][col].value() {
-                            if !seen.insert(value) {
-                                return false;
-                            }
-                        }
-                    }
-                }
-            }
+    /// Values not yet placed in any house of an empty cell, nor in its neighbours (zero
+    /// for filled cells), ignoring pencil mark eliminations
+    pub fn allowed_mask(&self, row: usize, col: usize) -> u32 {
+        if !self.cell(row, col).is_empty() {
+            return 0;
         }
-        true
+        let index = row * self.size + col;
+        let in_units = self
+            .layout
+            .units_of(index)
+            .iter()
+            .fold(0, |mask, &unit| mask | self.unit_masks[unit]);
+        let placed = self
+            .layout
+            .neighbours_of(index)
+            .iter()
+            .filter_map(|&other| self.cells[other].value())
+            .fold(in_units, |mask, value| mask | value_bit(value));
+        full_mask(self.size) & !placed
     }
 
-    pub fn is_complete(&self) -> bool {
+    /// Place a value without updating the pencil marks, for searches that undo it with
+    /// `unassign`; call `reset_candidates` once done
+    pub(crate) fn assign(&mut self, row: usize, col: usize, value: u8) {
+        self.put(row, col, Cell::Filled(value));
+    }
+
+    /// Clear a cell filled with `assign`
+    pub(crate) fn unassign(&mut self, row: usize, col: usize) {
+        self.put(row, col, Cell::Empty);
+    }
+
+    /// Recompute all pencil marks from the placed values, discarding previous eliminations
+    pub fn reset_candidates(&mut self) {
         for row in 0..self.size {
             for col in 0..self.size {
-                if self.grid[row][col].is_empty() {
-                    return false;
-                }
+                self.candidates[row * self.size + col] = self.allowed_mask(row, col);
             }
         }
-        true
     }
 
-    pub fn get_candidates(&self, row: usize, col: usize) -> HashSet<u8> {
-        if !self.grid[row][col].is_empty() {
-            return HashSet::new();
+    /// Update pencil marks after `value` was placed in an empty cell
+    fn place_candidate(&mut self, row: usize, col: usize, value: u8) {
+        let bit = !value_bit(value);
+        let index = row * self.size + col;
+
+        self.candidates[index] = 0;
+        for &peer in self.layout.peers_of(index) {
+            self.candidates[peer] &= bit;
         }
+    }
 
-        let mut candidates: HashSet<u8> = (1..=self.size as u8).collect();
+    pub fn is_valid(&self) -> bool {
+        self.duplicates == 0 && self.clashes == 0 && self.broken_cages == 0
+    }
 
-        // Remove values in the same row
-        for c in 0..self.size {
-            if let Some(value) = self.grid[row][c].value() {
-                candidates.remove(&value);
-            }
-        }
+    pub fn is_valid_rows(&self) -> bool {
+        self.unit_kind_is_valid(UnitKind::Row)
+    }
 
-        // Remove values in the same column
-        for r in 0..self.size {
-            if let Some(value) = self.grid[r][col].value() {
-                candidates.remove(&value);
-            }
-        }
+    pub fn is_valid_cols(&self) -> bool {
+        self.unit_kind_is_valid(UnitKind::Column)
+    }
 
-        // Remove values in the same box
-        let box_row = row / self.box_size;
-        let box_col = col / self.box_size;
-        for r in box_row * self.box_size..(box_row + 1) * self.box_size {
-            for c in box_col * self.box_size..(box_col + 1) * self.box_size {
-                if let Some(value) = self.grid[r][c].value() {
-                    candidates.remove(&value);
-                }
-            }
+    pub fn is_valid_boxes(&self) -> bool {
+        self.unit_kind_is_valid(UnitKind::Box)
+    }
+
+    /// Check the units of one kind for duplicates
+    fn unit_kind_is_valid(&self, kind: UnitKind) -> bool {
+        let size = self.size;
+        self.layout
+            .units()
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| unit.kind == kind)
+            .all(|(id, _)| {
+                self.counts[id * size..(id + 1) * size]
+                    .iter()
+                    .all(|&count| count <= 1)
+            })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.filled == self.size * self.size
+    }
+
+    /// Remaining candidates for a cell, including eliminations recorded in the pencil marks
+    pub fn get_candidates(&self, row: usize, col: usize) -> HashSet<u8> {
+        if !self.cell(row, col).is_empty() {
+            return HashSet::new();
         }
 
-        candidates
+        mask_values(self.candidate_mask(row, col)).collect()
     }
 
     /// Check if placing a value at the given position would be valid
@@ -236,44 +687,110 @@ impl Sudoku {
             return false;
         }
 
-        // Check if value already exists in the same row
-        for c in 0..self.size {
-            if c != col {
-                if let Some(cell) = self.get(row, c) {
-                    if cell.value() == Some(value) {
-                        return false;
-                    }
+        // The value must not already be placed in any house of the cell, other than here,
+        // nor in a neighbour
+        let own = usize::from(self.cell(row, col).value() == Some(value));
+        let size = self.size;
+        let index = value as usize - 1;
+        let cell = row * size + col;
+        self.layout
+            .units_of(cell)
+            .iter()
+            .all(|&unit| self.counts[unit * size + index] as usize <= own)
+            && self
+                .layout
+                .neighbours_of(cell)
+                .iter()
+                .all(|&other| self.cells[other].value() != Some(value))
+    }
+
+    /// Every repeated value, every cage adding up wrongly and every empty cell without
+    /// candidates
+    pub fn validate_detailed(&self) -> ValidationReport {
+        let mut conflicts = Vec::new();
+        let cages = (0..self.layout.cages().len()).map(|index| Unit::new(UnitKind::Cage, index));
+        for unit in self.units().into_iter().chain(cages) {
+            let cells = self.unit_cells(unit);
+            for value in 1..=self.size as u8 {
+                let holding: Vec<(usize, usize)> = cells
+                    .iter()
+                    .copied()
+                    .filter(|&(row, col)| self.cell(row, col).value() == Some(value))
+                    .collect();
+                if holding.len() > 1 {
+                    conflicts.push(Conflict {
+                        unit,
+                        value,
+                        cells: holding,
+                    });
                 }
             }
         }
 
-        // Check if value already exists in the same column
-        for r in 0..self.size {
-            if r != row {
-                if let Some(cell) = self.get(r, col) {
-                    if cell.value() == Some(value) {
-                        return false;
-                    }
+        // Neighbours sharing a house or a cage are already reported above
+        let mut clashes = Vec::new();
+        for cell in 0..self.size * self.size {
+            let value = match self.cells[cell].value() {
+                Some(value) => value,
+                None => continue,
+            };
+            let cage = self.layout.cage_index(cell / self.size, cell % self.size);
+            for &other in self.layout.neighbours_of(cell) {
+                let shares_unit = self
+                    .layout
+                    .units_of(cell)
+                    .iter()
+                    .any(|unit| self.layout.units_of(other).contains(unit))
+                    || (cage.is_some()
+                        && cage == self.layout.cage_index(other / self.size, other % self.size));
+                if other > cell && self.cells[other].value() == Some(value) && !shares_unit {
+                    clashes.push(Clash {
+                        value,
+                        cells: [
+                            (cell / self.size, cell % self.size),
+                            (other / self.size, other % self.size),
+                        ],
+                    });
                 }
             }
         }
 
-        // Check if value already exists in the same box
-        let box_row = row / self.box_size;
-        let box_col = col / self.box_size;
-        for r in box_row * self.box_size..(box_row + 1) * self.box_size {
-            for c in box_col * self.box_size..(box_col + 1) * self.box_size {
-                if r != row || c != col {
-                    if let Some(cell) = self.get(r, c) {
-                        if cell.value() == Some(value) {
-                            return false;
-                        }
-                    }
-                }
-            }
+        let dead_cells = (0..self.size)
+            .flat_map(|row| (0..self.size).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                self.cell(row, col).is_empty() && self.candidate_mask(row, col) == 0
+            })
+            .collect();
+
+        let cage_errors = self
+            .layout
+            .cages()
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| self.cage_is_broken(index))
+            .map(|(index, cage)| CageError {
+                cage: Unit::new(UnitKind::Cage, index),
+                total: self.cage_totals[index],
+                sum: cage.sum,
+                cells: cage.cells.clone(),
+            })
+            .collect();
+
+        ValidationReport {
+            conflicts,
+            clashes,
+            cage_errors,
+            dead_cells,
+            complete: self.is_complete(),
         }
+    }
 
-        true
+    /// Cells whose value is repeated elsewhere in one of their units, in row-major order
+    pub fn conflicting_cells(&self) -> Vec<(usize, usize)> {
+        if self.is_valid() {
+            return Vec::new();
+        }
+        self.validate_detailed().conflicting_cells()
     }
 
     /// Check if placing a value at the given position matches the correct solution
@@ -290,7 +807,8 @@ impl Sudoku {
         let mut original_puzzle = self.clone();
 
         // Clear the specific cell we're checking to get the original state
-        original_puzzle.grid[row][col] = crate::Cell::Empty;
+        original_puzzle.put(row, col, Cell::Empty);
+        original_puzzle.reset_candidates();
 
         // Try to solve the original puzzle
         use crate::SudokuSolver;
@@ -324,40 +842,128 @@ impl Sudoku {
         self.is_correct_placement(row, col, value)
     }
 
+    /// All houses of the grid: rows, columns, boxes, then any extra houses
+    pub fn units(&self) -> Vec<Unit> {
+        self.layout.units().to_vec()
+    }
+
+    /// Cells of a unit in row-major order
+    pub fn unit_cells(&self, unit: Unit) -> Vec<(usize, usize)> {
+        self.layout.unit_cells(unit)
+    }
+
+    /// The houses containing a cell: its row, column and box, then any extra houses
+    pub fn cell_units(&self, row: usize, col: usize) -> Vec<Unit> {
+        let units = self.layout.units();
+        self.layout
+            .units_of(row * self.size + col)
+            .iter()
+            .map(|&id| units[id])
+            .collect()
+    }
+
+    /// Index of the box containing a cell
+    pub fn box_index(&self, row: usize, col: usize) -> usize {
+        self.layout.box_index(row, col)
+    }
+
+    /// Check whether two distinct cells share a house
+    pub fn sees(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        self.layout
+            .peers_of(a.0 * self.size + a.1)
+            .binary_search(&(b.0 * self.size + b.1))
+            .is_ok()
+    }
+
     pub fn find_empty_cell(&self) -> Option<(usize, usize)> {
-        for row in 0..self.size {
-            for col in 0..self.size {
-                if self.grid[row][col].is_empty() {
-                    return Some((row, col));
+        self.cells
+            .iter()
+            .position(|cell| cell.is_empty())
+            .map(|i| (i / self.size, i % self.size))
+    }
+
+    /// `.` for an empty cell, else its value, with letters from 10
+    fn cell_char(&self, row: usize, col: usize) -> char {
+        match self.cell(row, col) {
+            Cell::Empty => '.',
+            Cell::Given(v) | Cell::Filled(v) if v <= 9 => (b'0' + v) as char,
+            Cell::Given(v) | Cell::Filled(v) => (b'A' + v - 10) as char,
+        }
+    }
+
+    /// Draw irregular boxes with a border wherever neighbouring cells are in different
+    /// boxes, and a corner where borders meet
+    fn fmt_jigsaw(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.size;
+        // Border between a cell and the one right of it, or below it
+        let right = |row: usize, col: usize| {
+            col + 1 < size && self.box_index(row, col) != self.box_index(row, col + 1)
+        };
+        let below = |row: usize, col: usize| {
+            row + 1 < size && self.box_index(row, col) != self.box_index(row + 1, col)
+        };
+
+        for row in 0..size {
+            if row > 0 {
+                let mut line = String::new();
+                for col in 0..size {
+                    if col > 0 {
+                        let vertical = right(row - 1, col - 1) || right(row, col - 1);
+                        let horizontal = below(row - 1, col - 1) || below(row - 1, col);
+                        line.push(match (vertical, horizontal) {
+                            (true, true) => '+',
+                            (true, false) => '|',
+                            (false, true) => '-',
+                            (false, false) => ' ',
+                        });
+                    }
+                    line.push(if below(row - 1, col) { '-' } else { ' ' });
                 }
+                writeln!(f, "{}", line.trim_end())?;
             }
+
+            for col in 0..size {
+                write!(f, "{}", self.cell_char(row, col))?;
+                if right(row, col) {
+                    write!(f, "|")?;
+                } else if col + 1 < size {
+                    write!(f, " ")?;
+                }
+            }
+            writeln!(f)?;
         }
-        None
+        Ok(())
     }
 }
 
 impl fmt::Display for Sudoku {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in 0..self.size {
-            if row > 0 && row % self.box_size == 0 {
-                writeln!(f, "{}", "-".repeat(self.size * 2 + self.box_size - 1))?;
+        if self.layout.is_jigsaw() {
+            return self.fmt_jigsaw(f);
+        }
+
+        // Lines go between rows or columns wherever every cell on one side is in another
+        // box than its neighbour on the other side
+        let size = self.size;
+        let row_line = |row: usize| {
+            row > 0 && (0..size).all(|col| self.box_index(row - 1, col) != self.box_index(row, col))
+        };
+        let col_line = |col: usize| {
+            col > 0 && (0..size).all(|row| self.box_index(row, col - 1) != self.box_index(row, col))
+        };
+        let width = size * 2 + (0..size).filter(|&col| col_line(col)).count();
+
+        for row in 0..size {
+            if row_line(row) {
+                writeln!(f, "{}", "-".repeat(width))?;
             }
 
-            for col in 0..self.size {
-                if col > 0 && col % self.box_size == 0 {
+            for col in 0..size {
+                if col_line(col) {
                     write!(f, "|")?;
                 }
 
-                match self.grid[row][col] {
-                    Cell::Empty => write!(f, ". ")?,
-                    Cell::Given(v) | Cell::Filled(v) => {
-                        if v <= 9 {
-                            write!(f, "{} ", v)?;
-                        } else {
-                            write!(f, "{} ", (b'A' + v - 10) as char)?;
-                        }
-                    }
-                }
+                write!(f, "{} ", self.cell_char(row, col))?;
             }
             writeln!(f)?;
         }
@@ -0,0 +1,296 @@
+//! Uniqueness strategies: Unique Rectangles and BUG+1.
+//!
+//! These rely on the puzzle having exactly one solution, so the solver only runs them
+//! when told so with `SudokuSolver::set_assume_unique_solution`. They also rely on swapping
+//! values between cells only mattering to the houses holding them, so they find nothing
+//! on grids whose neighbouring cells must differ, as in anti-knight Sudoku, or whose cages
+//! must add up to a sum, as in Killer Sudoku.
+
+use crate::strategies::{for_each_combination, push_step, Elimination, SolvingStrategy, Step};
+use crate::sudoku::{mask_values, value_bit, Sudoku, Unit, UnitKind};
+use crate::wings::eliminate_from_common_peers;
+
+/// Unique Rectangles: four empty cells in two rows, two columns and two boxes, with no
+/// other house holding just some of them, that all hold the same pair {a,b} would let a
+/// and b swap, giving two solutions. Types 1 to 6 avoid that "deadly pattern":
+///
+/// - Type 1: Three cells are exactly {a,b}, so a and b are removed from the fourth.
+/// - Types 2 and 5: Every other cell has the same one extra candidate c, so c is true in
+///   one of them and is removed from cells seeing all of them.
+/// - Type 3: The extras of two cells in a unit form a naked subset with other cells of it.
+/// - Type 4: Two cells in a unit hold the only places for a there, so b is removed from both.
+/// - Type 6: a is confined to the rectangle in both of its rows (or columns), so it is
+///   removed from the two diagonal cells holding extras.
+pub struct UniqueRectangles;
+
+impl UniqueRectangles {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let size = sudoku.size;
+        if sudoku.layout().has_neighbours() || sudoku.layout().is_killer() {
+            return steps;
+        }
+
+        for r1 in 0..size {
+            for r2 in r1 + 1..size {
+                for c1 in 0..size {
+                    for c2 in c1 + 1..size {
+                        let cells = [(r1, c1), (r1, c2), (r2, c1), (r2, c2)];
+                        let common = cells.iter().fold(u32::MAX, |mask, &(row, col)| {
+                            mask & sudoku.candidate_mask(row, col)
+                        });
+                        if common.count_ones() < 2 {
+                            continue;
+                        }
+
+                        // Swapping a and b only keeps every house valid if each house
+                        // holds two of the cells or none
+                        let swappable = cells.iter().all(|&(row, col)| {
+                            sudoku.cell_units(row, col).into_iter().all(|unit| {
+                                let unit_cells = sudoku.unit_cells(unit);
+                                cells
+                                    .iter()
+                                    .filter(|cell| unit_cells.contains(cell))
+                                    .count()
+                                    == 2
+                            })
+                        });
+                        if !swappable {
+                            continue;
+                        }
+                        let values: Vec<u8> = mask_values(common).collect();
+                        for (i, &a) in values.iter().enumerate() {
+                            for &b in &values[i + 1..] {
+                                let mut eliminations = Vec::new();
+                                self.rectangle(sudoku, cells, a, b, &mut eliminations);
+                                let step = Step::elimination(self.name(), eliminations)
+                                    .with_cells(cells.to_vec());
+                                push_step(&mut steps, step);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        steps
+    }
+
+    /// Eliminations for the rectangle `cells` on the pair {a,b}
+    fn rectangle(
+        &self,
+        sudoku: &Sudoku,
+        cells: [(usize, usize); 4],
+        a: u8,
+        b: u8,
+        eliminations: &mut Vec<Elimination>,
+    ) {
+        let pair = value_bit(a) | value_bit(b);
+        let extras = |&(row, col): &(usize, usize)| sudoku.candidate_mask(row, col) & !pair;
+        let (floor, roof): (Vec<_>, Vec<_>) = cells.iter().partition(|cell| extras(cell) == 0);
+
+        // Type 1
+        if floor.len() == 3 {
+            let (row, col) = roof[0];
+            eliminations.push(Elimination::new(row, col, a));
+            eliminations.push(Elimination::new(row, col, b));
+            return;
+        }
+
+        // Types 2 and 5
+        let roof_extras = roof.iter().fold(0, |mask, cell| mask | extras(cell));
+        if roof_extras.count_ones() == 1 && !floor.is_empty() {
+            let value = mask_values(roof_extras).next().unwrap();
+            eliminate_from_common_peers(sudoku, &roof, value, eliminations);
+        }
+
+        if floor.len() != 2 {
+            return;
+        }
+        let (p, q) = (roof[0], roof[1]);
+
+        if p.0 == q.0 || p.1 == q.1 {
+            // Types 3 and 4: the roof cells share a row or column (and maybe a box)
+            for unit in sudoku.cell_units(p.0, p.1) {
+                let unit_cells = sudoku.unit_cells(unit);
+                if !unit_cells.contains(&q) {
+                    continue;
+                }
+                let others: Vec<(usize, usize)> = unit_cells
+                    .into_iter()
+                    .filter(|&cell| cell != p && cell != q)
+                    .collect();
+
+                self.naked_subset(sudoku, &others, roof_extras, eliminations);
+
+                for (locked, removed) in [(a, b), (b, a)] {
+                    if others
+                        .iter()
+                        .all(|&(row, col)| !sudoku.has_candidate(row, col, locked))
+                    {
+                        eliminations.push(Elimination::new(p.0, p.1, removed));
+                        eliminations.push(Elimination::new(q.0, q.1, removed));
+                    }
+                }
+            }
+        } else {
+            // Type 6: the roof cells are diagonal
+            let rows = [p.0, q.0];
+            let cols = [p.1, q.1];
+            for value in [a, b] {
+                let confined =
+                    |lines: &[usize; 2], cell: &dyn Fn(usize, usize) -> (usize, usize)| {
+                        lines.iter().all(|&line| {
+                            (0..sudoku.size).all(|cross| {
+                                let (row, col) = cell(line, cross);
+                                !sudoku.has_candidate(row, col, value)
+                                    || (rows.contains(&row) && cols.contains(&col))
+                            })
+                        })
+                    };
+                if confined(&rows, &|line, cross| (line, cross))
+                    || confined(&cols, &|line, cross| (cross, line))
+                {
+                    eliminations.push(Elimination::new(p.0, p.1, value));
+                    eliminations.push(Elimination::new(q.0, q.1, value));
+                }
+            }
+        }
+    }
+
+    /// Type 3: treat the roof extras as one virtual cell and look for a naked subset with
+    /// `others`, the remaining cells of a unit holding both roof cells
+    fn naked_subset(
+        &self,
+        sudoku: &Sudoku,
+        others: &[(usize, usize)],
+        roof_extras: u32,
+        eliminations: &mut Vec<Elimination>,
+    ) {
+        let empty: Vec<(usize, usize)> = others
+            .iter()
+            .copied()
+            .filter(|&(row, col)| sudoku.candidate_count(row, col) > 0)
+            .collect();
+
+        for n in 1..=3 {
+            for_each_combination(empty.len(), n, |combo| {
+                let union = combo.iter().fold(roof_extras, |mask, &i| {
+                    mask | sudoku.candidate_mask(empty[i].0, empty[i].1)
+                });
+                if union.count_ones() as usize != n + 1 {
+                    return;
+                }
+                for (i, &(row, col)) in empty.iter().enumerate() {
+                    if combo.contains(&i) {
+                        continue;
+                    }
+                    for value in mask_values(union & sudoku.candidate_mask(row, col)) {
+                        eliminations.push(Elimination::new(row, col, value));
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl SolvingStrategy for UniqueRectangles {
+    fn name(&self) -> &'static str {
+        "Unique Rectangle"
+    }
+
+    fn rating(&self) -> f32 {
+        4.5
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+
+    fn requires_uniqueness(&self) -> bool {
+        true
+    }
+}
+
+/// BUG+1 (Bivalue Universal Grave): when every empty cell but one is bivalue and every
+/// candidate appears exactly twice in each unit, the grid would have two solutions unless
+/// the one trivalue cell takes the value appearing three times in its units
+pub struct BugPlusOne;
+
+impl BugPlusOne {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        if sudoku.layout().has_neighbours() || sudoku.layout().is_killer() {
+            return Vec::new();
+        }
+        let mut extra = None;
+        for row in 0..sudoku.size {
+            for col in 0..sudoku.size {
+                match sudoku.candidate_count(row, col) {
+                    0 | 2 => {}
+                    3 if extra.is_none() => extra = Some((row, col)),
+                    _ => return Vec::new(),
+                }
+            }
+        }
+        let (row, col) = match extra {
+            Some(cell) => cell,
+            None => return Vec::new(),
+        };
+
+        // The value appearing three times in the trivalue cell's row
+        let count = |cells: &[(usize, usize)], value: u8| {
+            cells
+                .iter()
+                .filter(|&&(r, c)| sudoku.has_candidate(r, c, value))
+                .count()
+        };
+        let row_cells = sudoku.unit_cells(Unit::new(UnitKind::Row, row));
+        let value = match sudoku
+            .candidate_values(row, col)
+            .into_iter()
+            .find(|&value| count(&row_cells, value) == 3)
+        {
+            Some(value) => value,
+            None => return Vec::new(),
+        };
+
+        // Every other candidate must appear exactly twice in each of its units
+        for unit in sudoku.units() {
+            let cells = sudoku.unit_cells(unit);
+            let holds_extra = cells.contains(&(row, col));
+            for v in 1..=sudoku.size as u8 {
+                let expected = if holds_extra && v == value { 3 } else { 2 };
+                let found = count(&cells, v);
+                if found != 0 && found != expected {
+                    return Vec::new();
+                }
+            }
+        }
+
+        let eliminations = sudoku
+            .candidate_values(row, col)
+            .into_iter()
+            .filter(|&v| v != value)
+            .map(|v| Elimination::new(row, col, v))
+            .collect();
+        vec![Step::elimination(self.name(), eliminations).with_cells(vec![(row, col)])]
+    }
+}
+
+impl SolvingStrategy for BugPlusOne {
+    fn name(&self) -> &'static str {
+        "BUG+1"
+    }
+
+    fn rating(&self) -> f32 {
+        5.6
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+
+    fn requires_uniqueness(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,117 @@
+//! Variant rules played on top of the rows, columns and boxes of a grid
+
+use crate::error::SudokuError;
+use crate::layout::Layout;
+use crate::sudoku::UnitKind;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A kind of house and its cells
+type House = (UnitKind, Vec<(usize, usize)>);
+
+/// An extra rule for a puzzle, added to a grid with `Layout::add_variant`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Variant {
+    /// X-Sudoku: both main diagonals hold every value once
+    Diagonal,
+    /// Windoku, or Hyper Sudoku: the windows one cell in from the boxes, four on a 9x9
+    /// grid, hold every value once
+    Windoku,
+    /// Cells a knight's move apart hold different values
+    AntiKnight,
+    /// Cells a king's move apart, diagonally too, hold different values
+    AntiKing,
+}
+
+impl Variant {
+    /// Name of the rule, as `from_str` reads it
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Diagonal => "diagonal",
+            Variant::Windoku => "windoku",
+            Variant::AntiKnight => "anti-knight",
+            Variant::AntiKing => "anti-king",
+        }
+    }
+
+    /// Read a list of rules separated by commas, such as `diagonal,anti-knight`
+    pub fn parse_list(s: &str) -> Result<Vec<Variant>, SudokuError> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// The houses the rule adds to a grid of `size`, each with its cells
+    pub(crate) fn houses(self, size: usize) -> Result<Vec<House>, SudokuError> {
+        match self {
+            Variant::Diagonal => Ok(vec![
+                (UnitKind::Diagonal, (0..size).map(|i| (i, i)).collect()),
+                (
+                    UnitKind::Diagonal,
+                    (0..size).map(|i| (i, size - 1 - i)).collect(),
+                ),
+            ]),
+            Variant::Windoku => {
+                let box_size = match Layout::box_shape_for(size) {
+                    Some((rows, cols)) if rows == cols && rows > 1 => rows,
+                    Some((rows, cols)) => {
+                        return Err(SudokuError::InvalidLayout(format!(
+                            "windoku needs square boxes, not {}x{}",
+                            rows, cols
+                        )))
+                    }
+                    None => return Err(SudokuError::InvalidSize(size)),
+                };
+                // Windows start one cell in from the edge, with a line of cells between them
+                let starts: Vec<usize> =
+                    (0..box_size - 1).map(|i| 1 + i * (box_size + 1)).collect();
+                let window = |top: usize, left: usize| {
+                    (top..top + box_size)
+                        .flat_map(|row| (left..left + box_size).map(move |col| (row, col)))
+                        .collect()
+                };
+                Ok(starts
+                    .iter()
+                    .flat_map(|&top| starts.iter().map(move |&left| (top, left)))
+                    .map(|(top, left)| (UnitKind::Window, window(top, left)))
+                    .collect())
+            }
+            Variant::AntiKnight | Variant::AntiKing => Ok(Vec::new()),
+        }
+    }
+
+    /// Moves, either way, between cells that must hold different values
+    pub(crate) fn moves(self) -> &'static [(isize, isize)] {
+        match self {
+            Variant::AntiKnight => &[(1, 2), (2, 1), (1, -2), (2, -1)],
+            // Orthogonal king moves stay in a row or column
+            Variant::AntiKing => &[(1, 1), (1, -1)],
+            Variant::Diagonal | Variant::Windoku => &[],
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = SudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "diagonal" | "x" | "x-sudoku" => Ok(Variant::Diagonal),
+            "windoku" | "hyper" => Ok(Variant::Windoku),
+            "anti-knight" | "antiknight" => Ok(Variant::AntiKnight),
+            "anti-king" | "antiking" => Ok(Variant::AntiKing),
+            _ => Err(SudokuError::UnknownVariant(s.to_string())),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! WebAssembly interface for the Sudoku solver with simple text-based UI
 
-use crate::{Difficulty, Sudoku, SudokuSolver};
+use crate::{Difficulty, GenerationOptions, Layout, Sudoku, SudokuSolver, Uniqueness, Variant};
 use wasm_bindgen::prelude::*;
 
 // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -22,29 +22,67 @@ pub struct WasmSudoku {
 
 #[wasm_bindgen]
 impl WasmSudoku {
+    /// An empty grid, with variant rules separated by commas if given, such as
+    /// "diagonal,anti-knight"
     #[wasm_bindgen(constructor)]
-    pub fn new(size: usize) -> WasmSudoku {
+    pub fn new(size: usize, variants: Option<String>) -> Result<WasmSudoku, JsError> {
         #[cfg(feature = "console_error_panic_hook")]
         set_panic_hook();
 
-        WasmSudoku {
-            sudoku: Sudoku::new(size),
+        Ok(WasmSudoku {
+            sudoku: Sudoku::with_layout(variant_layout(
+                Layout::standard(size)?,
+                variants.as_deref(),
+            )?),
             solver: SudokuSolver::new(),
-        }
+        })
     }
 
+    /// An empty grid of boxes `box_rows` tall and `box_cols` wide, such as 3x2 boxes on a
+    /// 6x6 grid, with variant rules as for `new`
     #[wasm_bindgen]
-    pub fn from_string(puzzle_str: &str, size: usize) -> Result<WasmSudoku, String> {
+    pub fn with_box_shape(
+        box_rows: usize,
+        box_cols: usize,
+        variants: Option<String>,
+    ) -> Result<WasmSudoku, JsError> {
         #[cfg(feature = "console_error_panic_hook")]
         set_panic_hook();
 
-        match Sudoku::from_string(puzzle_str, size) {
-            Ok(sudoku) => Ok(WasmSudoku {
-                sudoku,
-                solver: SudokuSolver::new(),
-            }),
-            Err(e) => Err(format!("Failed to parse sudoku: {}", e)),
-        }
+        let layout = Layout::rectangular(box_rows, box_cols)?;
+        Ok(WasmSudoku {
+            sudoku: Sudoku::with_layout(variant_layout(layout, variants.as_deref())?),
+            solver: SudokuSolver::new(),
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn from_string(
+        puzzle_str: &str,
+        size: usize,
+        variants: Option<String>,
+    ) -> Result<WasmSudoku, JsError> {
+        #[cfg(feature = "console_error_panic_hook")]
+        set_panic_hook();
+
+        let layout = variant_layout(Layout::standard(size)?, variants.as_deref())?;
+        Ok(WasmSudoku {
+            sudoku: Sudoku::from_string_with_layout(puzzle_str, layout)?,
+            solver: SudokuSolver::new(),
+        })
+    }
+
+    /// The variant rules of the grid, such as "diagonal,anti-knight"
+    #[wasm_bindgen]
+    pub fn variants(&self) -> String {
+        let names: Vec<&str> = self
+            .sudoku
+            .layout()
+            .variants()
+            .iter()
+            .map(|variant| variant.name())
+            .collect();
+        names.join(",")
     }
 
     #[wasm_bindgen]
@@ -52,6 +90,18 @@ impl WasmSudoku {
         self.sudoku.size
     }
 
+    /// Rows in each box
+    #[wasm_bindgen]
+    pub fn get_box_rows(&self) -> usize {
+        self.sudoku.box_rows
+    }
+
+    /// Columns in each box
+    #[wasm_bindgen]
+    pub fn get_box_cols(&self) -> usize {
+        self.sudoku.box_cols
+    }
+
     #[wasm_bindgen]
     pub fn get_value(&self, row: usize, col: usize) -> u8 {
         self.sudoku
@@ -61,10 +111,8 @@ impl WasmSudoku {
     }
 
     #[wasm_bindgen]
-    pub fn set_value(&mut self, row: usize, col: usize, value: u8) -> Result<(), String> {
-        self.sudoku
-            .set(row, col, value)
-            .map_err(|e| format!("Failed to set value: {}", e))
+    pub fn set_value(&mut self, row: usize, col: usize, value: u8) -> Result<(), JsError> {
+        Ok(self.sudoku.set(row, col, value)?)
     }
 
     #[wasm_bindgen]
@@ -72,6 +120,47 @@ impl WasmSudoku {
         self.sudoku.is_valid()
     }
 
+    /// Cells that share their value with another cell in a unit, as row * size + col
+    #[wasm_bindgen]
+    pub fn conflicting_cells(&self) -> Vec<usize> {
+        let size = self.sudoku.size;
+        self.sudoku
+            .validate_detailed()
+            .conflicting_cells()
+            .into_iter()
+            .map(|(row, col)| row * size + col)
+            .collect()
+    }
+
+    /// Empty cells with no candidates left, as row * size + col
+    #[wasm_bindgen]
+    pub fn dead_cells(&self) -> Vec<usize> {
+        let size = self.sudoku.size;
+        self.sudoku
+            .validate_detailed()
+            .dead_cells
+            .into_iter()
+            .map(|(row, col)| row * size + col)
+            .collect()
+    }
+
+    /// One line per problem, e.g. "7 appears 2 times in row 9: r9c8, r9c9"
+    #[wasm_bindgen]
+    pub fn validation_report(&self) -> Vec<String> {
+        let report = self.sudoku.validate_detailed();
+        let conflicts = report
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.to_string())
+            .chain(report.clashes.iter().map(|clash| clash.to_string()))
+            .chain(report.cage_errors.iter().map(|error| error.to_string()));
+        let dead = report
+            .dead_cells
+            .iter()
+            .map(|&(row, col)| format!("r{}c{} has no candidates left", row + 1, col + 1));
+        conflicts.chain(dead).collect()
+    }
+
     #[wasm_bindgen]
     pub fn is_complete(&self) -> bool {
         self.sudoku.is_complete()
@@ -98,41 +187,88 @@ impl WasmSudoku {
     }
 
     #[wasm_bindgen]
-    pub fn solve(&mut self) -> Result<(), String> {
-        match self.solver.solve(self.sudoku.clone()) {
-            Ok(solution) => {
-                self.sudoku = solution;
-                Ok(())
-            }
-            Err(e) => Err(format!("Failed to solve: {}", e)),
-        }
+    pub fn solve(&mut self) -> Result<(), JsError> {
+        self.sudoku = self.solver.solve(self.sudoku.clone())?;
+        Ok(())
     }
 
     #[wasm_bindgen]
     pub fn get_hint(&mut self) -> Option<String> {
-        match self.solver.get_hint(&mut self.sudoku.clone()) {
-            Some((row, col, value)) => Some(format!("{}:{}:{}", row, col, value)),
-            None => None,
-        }
+        self.solver.hint_step(&self.sudoku).map(|step| {
+            let placement = step.placements[0];
+            format!("{}:{}:{}", placement.row, placement.col, placement.value)
+        })
     }
 
+    /// Describe the next logical step, e.g. "Naked Pairs in row 1: r1c5<>3"
     #[wasm_bindgen]
-    pub fn generate_puzzle(&mut self, difficulty: &str) -> Result<(), String> {
-        let diff = match difficulty.to_lowercase().as_str() {
-            "easy" => Difficulty::Easy,
-            "medium" => Difficulty::Medium,
-            "hard" => Difficulty::Hard,
-            "expert" => Difficulty::Expert,
-            _ => return Err("Invalid difficulty. Use easy, medium, hard, or expert".to_string()),
-        };
+    pub fn next_step(&self) -> Option<String> {
+        self.solver
+            .next_step(&self.sudoku)
+            .map(|step| step.to_string())
+    }
 
-        match self.solver.generate_puzzle(self.sudoku.size, diff) {
-            Ok(puzzle) => {
-                self.sudoku = puzzle;
-                Ok(())
-            }
-            Err(e) => Err(format!("Failed to generate puzzle: {}", e)),
+    /// Whether the puzzle has "none", a "unique" or "multiple" solutions
+    #[wasm_bindgen]
+    pub fn check(&mut self) -> String {
+        match self.solver.analyze(&self.sudoku).uniqueness {
+            Uniqueness::NoSolution => "none",
+            Uniqueness::Unique(_) => "unique",
+            Uniqueness::Multiple(_, _) => "multiple",
         }
+        .to_string()
+    }
+
+    /// Givens that could each be removed with the solution staying unique, as row * size + col
+    #[wasm_bindgen]
+    pub fn redundant_givens(&mut self) -> Vec<usize> {
+        let size = self.sudoku.size;
+        self.solver
+            .analyze(&self.sudoku)
+            .redundant_givens
+            .into_iter()
+            .map(|(row, col)| row * size + col)
+            .collect()
+    }
+
+    /// Cells where two solutions of an ambiguous puzzle differ, as row * size + col
+    #[wasm_bindgen]
+    pub fn ambiguous_cells(&mut self) -> Vec<usize> {
+        let size = self.sudoku.size;
+        self.solver
+            .analyze(&self.sudoku)
+            .ambiguous_cells()
+            .into_iter()
+            .map(|(row, col)| row * size + col)
+            .collect()
+    }
+
+    /// Number of given cells
+    #[wasm_bindgen]
+    pub fn clue_count(&self) -> usize {
+        self.sudoku.clue_count()
+    }
+
+    /// Generate a puzzle with the variant rules of the grid
+    #[wasm_bindgen]
+    pub fn generate_puzzle(&mut self, difficulty: &str) -> Result<(), JsError> {
+        let diff: Difficulty = difficulty.parse()?;
+        let options = self.generation_options(GenerationOptions::new(diff));
+        self.sudoku = self
+            .solver
+            .generate_with_options(self.sudoku.size, &options)?;
+        Ok(())
+    }
+
+    /// Generate a puzzle reproducibly from `seed`
+    #[wasm_bindgen]
+    pub fn generate_puzzle_seeded(&mut self, difficulty: &str, seed: u64) -> Result<(), JsError> {
+        let options =
+            self.generation_options(GenerationOptions::new(difficulty.parse()?).with_seed(seed));
+        self.sudoku = self
+            .solver
+            .generate_with_options(self.sudoku.size, &options)?;
+        Ok(())
     }
 
     /// Render the sudoku grid as a simple text representation
@@ -141,13 +277,14 @@ impl WasmSudoku {
         let mut result = String::new();
 
         for row in 0..self.sudoku.size {
-            if row > 0 && row % self.sudoku.box_size == 0 {
-                result.push_str(&"-".repeat(self.sudoku.size * 3 + self.sudoku.box_size - 1));
+            if row > 0 && row % self.sudoku.box_rows == 0 {
+                let stacks = self.sudoku.size / self.sudoku.box_cols;
+                result.push_str(&"-".repeat(self.sudoku.size * 3 + stacks - 1));
                 result.push('\n');
             }
 
             for col in 0..self.sudoku.size {
-                if col > 0 && col % self.sudoku.box_size == 0 {
+                if col > 0 && col % self.sudoku.box_cols == 0 {
                     result.push('|');
                 }
 
@@ -181,10 +318,33 @@ impl WasmSudoku {
     }
 }
 
+impl WasmSudoku {
+    /// `options` with the boxes and variant rules of the grid
+    fn generation_options(&self, options: GenerationOptions) -> GenerationOptions {
+        let layout = self.sudoku.layout();
+        let options = match layout.box_shape() {
+            Some((box_rows, box_cols)) => options.with_box_shape(box_rows, box_cols),
+            None => options,
+        };
+        layout
+            .variants()
+            .iter()
+            .fold(options, |options, &variant| options.with_variant(variant))
+    }
+}
+
+/// `layout` with the variant rules listed in `variants`
+fn variant_layout(mut layout: Layout, variants: Option<&str>) -> Result<Layout, JsError> {
+    for variant in Variant::parse_list(variants.unwrap_or_default())? {
+        layout.add_variant(variant)?;
+    }
+    Ok(layout)
+}
+
 // Additional utility functions for WASM
 #[wasm_bindgen]
 pub fn create_example_puzzle() -> WasmSudoku {
     let puzzle_str =
         "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
-    WasmSudoku::from_string(puzzle_str, 9).unwrap()
+    WasmSudoku::from_string(puzzle_str, 9, None).unwrap()
 }
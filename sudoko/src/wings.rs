@@ -0,0 +1,198 @@
+//! Wing strategies built from bivalue cells: XY-Wing, XYZ-Wing and W-Wing
+
+use crate::chains::conjugate_pairs;
+use crate::strategies::{push_step, Elimination, SolvingStrategy, Step};
+use crate::sudoku::{mask_values, value_bit, Sudoku};
+
+/// Empty cells with exactly `count` candidates
+fn cells_with_candidate_count(sudoku: &Sudoku, count: usize) -> Vec<(usize, usize)> {
+    (0..sudoku.size)
+        .flat_map(|row| (0..sudoku.size).map(move |col| (row, col)))
+        .filter(|&(row, col)| {
+            sudoku.cell(row, col).is_empty() && sudoku.candidate_count(row, col) == count
+        })
+        .collect()
+}
+
+/// Remove `value` from every cell that sees all of `cells`
+pub(crate) fn eliminate_from_common_peers(
+    sudoku: &Sudoku,
+    cells: &[(usize, usize)],
+    value: u8,
+    eliminations: &mut Vec<Elimination>,
+) {
+    for row in 0..sudoku.size {
+        for col in 0..sudoku.size {
+            if sudoku.has_candidate(row, col, value)
+                && cells.iter().all(|&cell| sudoku.sees((row, col), cell))
+            {
+                eliminations.push(Elimination::new(row, col, value));
+            }
+        }
+    }
+}
+
+/// XY-Wing: A bivalue pivot {x,y} sees pincers {x,z} and {y,z}; whichever value the pivot
+/// takes, one pincer is z, so z can be removed from cells seeing both pincers
+pub struct XYWing;
+
+impl XYWing {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let bivalue = cells_with_candidate_count(sudoku, 2);
+
+        for &pivot in &bivalue {
+            let pivot_mask = sudoku.candidate_mask(pivot.0, pivot.1);
+            let wings: Vec<(usize, usize)> = bivalue
+                .iter()
+                .copied()
+                .filter(|&cell| sudoku.sees(pivot, cell))
+                .filter(|&(row, col)| {
+                    (sudoku.candidate_mask(row, col) & pivot_mask).count_ones() == 1
+                })
+                .collect();
+
+            for (i, &a) in wings.iter().enumerate() {
+                for &b in &wings[i + 1..] {
+                    let mask_a = sudoku.candidate_mask(a.0, a.1);
+                    let mask_b = sudoku.candidate_mask(b.0, b.1);
+                    let z = mask_a & mask_b & !pivot_mask;
+                    // Pincers must share z and cover both pivot values between them
+                    if z.count_ones() != 1 || (mask_a | mask_b) & pivot_mask != pivot_mask {
+                        continue;
+                    }
+                    let value = mask_values(z).next().unwrap();
+                    let mut eliminations = Vec::new();
+                    eliminate_from_common_peers(sudoku, &[a, b], value, &mut eliminations);
+                    let step =
+                        Step::elimination(self.name(), eliminations).with_cells(vec![pivot, a, b]);
+                    push_step(&mut steps, step);
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+impl SolvingStrategy for XYWing {
+    fn name(&self) -> &'static str {
+        "XY-Wing"
+    }
+
+    fn rating(&self) -> f32 {
+        4.2
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
+
+/// XYZ-Wing: A pivot {x,y,z} sees pincers {x,z} and {y,z}; one of the three cells is z,
+/// so z can be removed from cells seeing all three
+pub struct XYZWing;
+
+impl XYZWing {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let bivalue = cells_with_candidate_count(sudoku, 2);
+
+        for pivot in cells_with_candidate_count(sudoku, 3) {
+            let pivot_mask = sudoku.candidate_mask(pivot.0, pivot.1);
+            let wings: Vec<(usize, usize)> = bivalue
+                .iter()
+                .copied()
+                .filter(|&cell| sudoku.sees(pivot, cell))
+                .filter(|&(row, col)| sudoku.candidate_mask(row, col) & !pivot_mask == 0)
+                .collect();
+
+            for (i, &a) in wings.iter().enumerate() {
+                for &b in &wings[i + 1..] {
+                    let mask_a = sudoku.candidate_mask(a.0, a.1);
+                    let mask_b = sudoku.candidate_mask(b.0, b.1);
+                    let z = mask_a & mask_b;
+                    if z.count_ones() != 1 || mask_a | mask_b != pivot_mask {
+                        continue;
+                    }
+                    let value = mask_values(z).next().unwrap();
+                    let mut eliminations = Vec::new();
+                    eliminate_from_common_peers(sudoku, &[pivot, a, b], value, &mut eliminations);
+                    let step =
+                        Step::elimination(self.name(), eliminations).with_cells(vec![pivot, a, b]);
+                    push_step(&mut steps, step);
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+impl SolvingStrategy for XYZWing {
+    fn name(&self) -> &'static str {
+        "XYZ-Wing"
+    }
+
+    fn rating(&self) -> f32 {
+        4.4
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
+
+/// W-Wing: Two identical bivalue cells {x,y} joined by a strong link on x; one of them
+/// must be y, so y can be removed from cells seeing both
+pub struct WWing;
+
+impl WWing {
+    fn find(&self, sudoku: &Sudoku) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let bivalue = cells_with_candidate_count(sudoku, 2);
+
+        for (i, &a) in bivalue.iter().enumerate() {
+            let mask = sudoku.candidate_mask(a.0, a.1);
+            for &b in &bivalue[i + 1..] {
+                if sudoku.candidate_mask(b.0, b.1) != mask || sudoku.sees(a, b) {
+                    continue;
+                }
+
+                for x in mask_values(mask) {
+                    let y = mask_values(mask & !value_bit(x)).next().unwrap();
+                    let link = conjugate_pairs(sudoku, x).into_iter().find(|&(c, d)| {
+                        let ends = [a, b];
+                        !ends.contains(&c)
+                            && !ends.contains(&d)
+                            && ((sudoku.sees(a, c) && sudoku.sees(b, d))
+                                || (sudoku.sees(a, d) && sudoku.sees(b, c)))
+                    });
+                    if let Some((c, d)) = link {
+                        let mut eliminations = Vec::new();
+                        eliminate_from_common_peers(sudoku, &[a, b], y, &mut eliminations);
+                        let step = Step::elimination(self.name(), eliminations)
+                            .with_cells(vec![a, b, c, d]);
+                        push_step(&mut steps, step);
+                    }
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+impl SolvingStrategy for WWing {
+    fn name(&self) -> &'static str {
+        "W-Wing"
+    }
+
+    fn rating(&self) -> f32 {
+        4.4
+    }
+
+    fn find_steps(&self, sudoku: &Sudoku) -> Vec<Step> {
+        self.find(sudoku)
+    }
+}
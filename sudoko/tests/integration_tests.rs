@@ -1,4 +1,21 @@
-use sudoko::{Sudoku, SudokuSolver};
+use sudoko::{
+    AlternatingInferenceChains, BatchSolver, Cage, generate_batch, BoxLineReduction, NakedSingles, NakedTriples, Difficulty, mask_values, Elimination, Fish, GenerationOptions, HiddenPairs, Layout, NakedPairs, Placement, PointingPairs, SimpleColoring,
+    SolverBackend, SolvingStrategy, Step, Sudoku, SudokuError, SudokuSolver, Symmetry, Uniqueness, Unit, UnitKind, UniqueRectangles, Variant, WWing, XChains, XWing, XYWing, XYZWing,
+};
+use std::time::Duration;
+
+/// Empty 9x9 grid with pencil marks limited to the given cells' candidates
+fn grid_with_candidates(cells: &[((usize, usize), &[u8])]) -> Sudoku {
+    let mut puzzle = Sudoku::new(9);
+    for &((row, col), keep) in cells {
+        for value in 1..=9 {
+            if !keep.contains(&value) {
+                puzzle.eliminate_candidate(row, col, value);
+            }
+        }
+    }
+    puzzle
+}
 
 #[test]
 fn test_9x9_basic_solve() {
@@ -24,10 +41,21 @@ fn test_4x4_solve() {
     // Test basic 4x4 structure
     let puzzle = Sudoku::new(4);
     assert_eq!(puzzle.size, 4);
-    assert_eq!(puzzle.box_size, 2);
+    assert_eq!((puzzle.box_rows, puzzle.box_cols), (2, 2));
     assert!(!puzzle.is_complete());
 }
 
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_grid_accessors() {
+    let puzzle = Sudoku::from_string("1234341221434321", 4).unwrap();
+    let grid = puzzle.grid();
+    assert_eq!(grid.len(), 4);
+    assert!(grid.iter().all(|row| row.len() == 4));
+    assert_eq!(grid[1][2], sudoko::Cell::Given(1));
+    assert_eq!(puzzle.box_size(), 2);
+}
+
 #[test]
 fn test_validation() {
     // Valid complete solution
@@ -42,6 +70,78 @@ fn test_validation() {
     assert!(!invalid_puzzle.is_valid());
 }
 
+#[test]
+fn test_unit_masks_track_placements() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let mut puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+    assert_eq!(puzzle.empty_cells(), 51);
+
+    // Row 1 holds 3, 5, 7; column 3 holds 8; box 1 holds 3, 5, 6, 8, 9
+    let allowed: Vec<u8> = mask_values(puzzle.allowed_mask(0, 2)).collect();
+    assert_eq!(allowed, vec![1, 2, 4]);
+    assert!(puzzle.is_valid_placement(0, 2, 4));
+    assert!(!puzzle.is_valid_placement(0, 2, 9));
+
+    // A duplicate is tracked until it is cleared again
+    puzzle.set(0, 2, 9).unwrap();
+    assert!(!puzzle.is_valid());
+    assert!(!puzzle.is_valid_boxes());
+    assert!(puzzle.is_valid_rows());
+    puzzle.set(0, 2, 0).unwrap();
+    assert!(puzzle.is_valid());
+    assert_eq!(puzzle.empty_cells(), 51);
+    assert_eq!(puzzle.allowed_mask(0, 2), puzzle.candidate_mask(0, 2));
+}
+
+#[test]
+fn test_16x16_backtracking() {
+    let puzzle_str = "A..49.7.....BCG5.DC.E64..9..F73.....25.3.8.4.......5AD.G..C...2.93.G7...BA..EF..4B1......5F3A.......8.3F.......2.2..BA..........C...1......G....3.......F7.E8......84.6......51D..AD..8C...52.E.......G.2.5A........6.2.9.B8.4...F4A..E..G...28.2CB7.8...4..1..G";
+    let puzzle = Sudoku::from_string(puzzle_str, 16).unwrap();
+    let mut solver = SudokuSolver::new_with_strategies(vec![]);
+
+    let solution = solver.solve(puzzle.clone()).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert_eq!(solver.count_solutions(puzzle, 2), 2);
+}
+
+#[test]
+fn test_dancing_links_backend() {
+    let puzzle_str = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+    let mut dlx = SudokuSolver::new();
+    dlx.set_backend(SolverBackend::DancingLinks);
+
+    let solution = dlx.solve(puzzle.clone()).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert_eq!(solution.to_string(), SudokuSolver::new().solve(puzzle.clone()).unwrap().to_string());
+    assert_eq!(dlx.count_solutions(puzzle, 2), 1);
+
+    // Invalid grids have no solutions
+    let invalid = Sudoku::from_string("11..............", 4).unwrap();
+    assert_eq!(dlx.count_solutions(invalid, 2), 0);
+}
+
+#[test]
+fn test_find_all_solutions() {
+    // Two rows left open in a 4x4 grid can be filled in two ways
+    let puzzle = Sudoku::from_string("12343412........", 4).unwrap();
+
+    for backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks] {
+        let mut solver = SudokuSolver::new();
+        solver.set_backend(backend);
+        let mut solutions: Vec<String> = solver
+            .find_solutions(puzzle.clone(), usize::MAX)
+            .iter()
+            .map(|solution| solution.to_string())
+            .collect();
+        solutions.sort();
+        solutions.dedup();
+        assert_eq!(solutions.len(), 4);
+        assert_eq!(solver.count_solutions(puzzle.clone(), usize::MAX), 4);
+        assert_eq!(solver.find_solutions(puzzle.clone(), 3).len(), 3);
+    }
+}
+
 #[test]
 fn test_candidates() {
     let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
@@ -66,7 +166,7 @@ fn test_hint_system() {
     if let Some((row, col, value)) = hint {
         assert!(row < 9);
         assert!(col < 9);
-        assert!(value >= 1 && value <= 9);
+        assert!((1..=9).contains(&value));
         
         // Verify the hint is valid
         let candidates = puzzle.get_candidates(row, col);
@@ -78,34 +178,388 @@ fn test_hint_system() {
 fn test_empty_puzzle_creation() {
     let sudoku = Sudoku::new(9);
     assert_eq!(sudoku.size, 9);
-    assert_eq!(sudoku.box_size, 3);
+    assert_eq!((sudoku.box_rows, sudoku.box_cols), (3, 3));
     assert!(!sudoku.is_complete());
     
     // All cells should be empty
     for row in 0..9 {
         for col in 0..9 {
-            assert!(sudoku.grid[row][col].is_empty());
+            assert!(sudoku.cell(row, col).is_empty());
         }
     }
 }
 
 #[test]
 fn test_invalid_size() {
-    // Test with invalid size (not a perfect square)
-    std::panic::catch_unwind(|| {
-        Sudoku::new(10);
+    // Test with invalid size (does not split into boxes)
+    let panic = std::panic::catch_unwind(|| {
+        Sudoku::new(7);
     }).unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>(), Some(&SudokuError::InvalidSize(7).to_string()));
+    assert_eq!(Layout::standard(7).unwrap_err(), SudokuError::InvalidSize(7));
+
+    // Sizes that are not perfect squares, such as 10, now take rectangular boxes
+    let sudoku = Sudoku::new(10);
+    assert_eq!(sudoku.size, 10);
+    assert_eq!((sudoku.box_rows, sudoku.box_cols), (2, 5));
+    assert!(!sudoku.is_complete());
 }
 
 #[test]
 fn test_from_string_errors() {
     // Test with wrong length
     let result = Sudoku::from_string("123", 9);
-    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), SudokuError::InvalidLength { expected: 81, found: 3 });
     
     // Test with invalid characters
     let result = Sudoku::from_string("53007000060019500009800006080006000340080300170002000606000028000041900500008007X", 9);
-    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), SudokuError::InvalidCharacter { row: 8, col: 8, character: 'X' });
+
+    // Values too large for the grid are not valid characters
+    let result = Sudoku::from_string("5...............", 4);
+    assert_eq!(result.unwrap_err(), SudokuError::InvalidCharacter { row: 0, col: 0, character: '5' });
+
+    assert_eq!(Sudoku::from_string("123", 3).unwrap_err(), SudokuError::InvalidSize(3));
+}
+
+#[test]
+fn test_typed_errors() {
+    let mut puzzle = Sudoku::new(4);
+    assert_eq!(puzzle.set(4, 0, 1), Err(SudokuError::InvalidPosition { row: 4, col: 0 }));
+    assert_eq!(puzzle.set(0, 0, 5), Err(SudokuError::ValueOutOfRange { value: 5, size: 4 }));
+
+    // Every cell sharing a value with another in a unit is reported
+    let puzzle = Sudoku::from_string("1..1....1.......", 4).unwrap();
+    let error = SudokuSolver::new().solve(puzzle).unwrap_err();
+    assert_eq!(error, SudokuError::ContradictoryGivens { cells: vec![(0, 0), (0, 3), (2, 0)] });
+    assert_eq!(error.to_string(), "Contradictory givens at r1c1, r1c4, r3c1");
+
+    // Valid givens that cannot be completed: r1c4 can only be 4, which r2c4 already holds
+    let puzzle = Sudoku::from_string("123....4........", 4).unwrap();
+    assert_eq!(SudokuSolver::new().solve(puzzle).unwrap_err(), SudokuError::Unsolvable);
+
+    let puzzle = Sudoku::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......", 9).unwrap();
+    let mut solver = SudokuSolver::new();
+    solver.set_use_backtracking(false);
+    solver.set_max_iterations(2);
+    assert_eq!(solver.solve(puzzle.clone()).unwrap_err(), SudokuError::IterationLimit(2));
+
+    for backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks] {
+        let mut solver = SudokuSolver::new_with_strategies(vec![]);
+        solver.set_backend(backend);
+        solver.set_time_limit(Some(Duration::ZERO));
+        assert_eq!(solver.solve(puzzle.clone()).unwrap_err(), SudokuError::TimeLimit(Duration::ZERO));
+    }
+}
+
+#[test]
+fn test_library_functions() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    let (solution, stats) = sudoko::solve_puzzle(puzzle_str, 9).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.cells_filled > 0);
+
+    let report = sudoko::validate_puzzle(puzzle_str, 9).unwrap();
+    assert!(report.is_valid() && !report.complete && !report.is_solved());
+    let report = sudoko::validate_puzzle("534678912672195348198342567859761423426853791713924856961537284287419635345286177", 9).unwrap();
+    assert!(!report.is_valid() && !report.valid_boxes());
+
+    let hint = sudoko::get_hint(puzzle_str, 9).unwrap().unwrap();
+    assert_eq!(solution.cell(hint.row, hint.col).value(), Some(hint.value));
+    assert_eq!(Some(&hint.step), SudokuSolver::new().next_step(&Sudoku::from_string(puzzle_str, 9).unwrap()).as_ref());
+    let placing = SudokuSolver::new().hint_step(&Sudoku::from_string(puzzle_str, 9).unwrap()).unwrap();
+    assert_eq!(placing.placements[0], Placement::new(hint.row, hint.col, hint.value));
+
+    assert!(matches!(sudoko::solve_from_file("/nonexistent/puzzle.txt", 9), Err(SudokuError::Io(_))));
+    assert_eq!(sudoko::generate_puzzle(7, Difficulty::Easy).unwrap_err(), SudokuError::InvalidSize(7));
+    let ten = sudoko::generate_puzzle_seeded(10, Difficulty::Easy, 10).unwrap();
+    assert_eq!((ten.box_rows, ten.box_cols), (2, 5));
+    assert_eq!("Hard".parse::<Difficulty>().unwrap(), Difficulty::Hard);
+    assert_eq!("bogus".parse::<Difficulty>().unwrap_err(), SudokuError::InvalidDifficulty("bogus".to_string()));
+}
+
+#[test]
+fn test_validate_detailed() {
+    let puzzle = Sudoku::from_string("123.1......4....", 4).unwrap();
+    let report = puzzle.validate_detailed();
+
+    assert_eq!(report.conflicts.len(), 2);
+    assert_eq!(report.conflicts[0].unit, Unit::new(UnitKind::Column, 0));
+    assert_eq!(report.conflicts[0].value, 1);
+    assert_eq!(report.conflicts[0].cells, vec![(0, 0), (1, 0)]);
+    assert_eq!(report.conflicts[1].unit, Unit::new(UnitKind::Box, 0));
+    assert_eq!(report.conflicts[0].to_string(), "1 appears 2 times in column 1: r1c1, r2c1");
+    assert_eq!(report.conflicting_cells(), vec![(0, 0), (1, 0)]);
+    assert!(report.valid_rows() && !report.valid_cols() && !report.valid_boxes());
+    assert_eq!(report.dead_cells, vec![(0, 3)]);
+    assert_eq!(puzzle.conflicting_cells(), report.conflicting_cells());
+
+    let report = Sudoku::from_string("123........4....", 4).unwrap().validate_detailed();
+    assert!(report.conflicts.is_empty() && report.is_valid() && !report.is_solved());
+    assert_eq!(report.dead_cells, vec![(0, 3)]);
+}
+
+#[test]
+fn test_uniqueness_analysis() {
+    let puzzle_str = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+    let analysis = sudoko::check_puzzle(puzzle_str, 9).unwrap();
+    assert!(analysis.is_unique() && analysis.is_minimal());
+    assert!(analysis.ambiguous_cells().is_empty());
+
+    let analysis = sudoko::check_puzzle("530070000600195000098000060800060003400803001700020006060000280000419005000080079", 9).unwrap();
+    assert!(analysis.is_unique() && !analysis.is_minimal());
+    assert_eq!(analysis.redundant_givens.len(), 22);
+    assert_eq!(analysis.redundant_givens[0], (0, 0));
+
+    // Without the 5 and 3 in the top left the puzzle has several solutions
+    let analysis = sudoko::check_puzzle("000070000600195000098000060800060003400803001700020006060000280000419005000080079", 9).unwrap();
+    match &analysis.uniqueness {
+        Uniqueness::Multiple(first, second) => {
+            assert!(first.is_complete() && first.is_valid() && second.is_complete() && second.is_valid());
+        }
+        other => panic!("expected multiple solutions, got {:?}", other),
+    }
+    assert!(!analysis.is_unique() && !analysis.is_minimal() && analysis.redundant_givens.is_empty());
+    assert_eq!(analysis.ambiguous_cells(), vec![(0, 0), (0, 1), (0, 2), (8, 0), (8, 1), (8, 2)]);
+
+    let mut solver = SudokuSolver::new();
+    let analysis = solver.analyze(&Sudoku::from_string("11..............", 4).unwrap());
+    assert!(matches!(analysis.uniqueness, Uniqueness::NoSolution));
+}
+
+#[test]
+fn test_generated_puzzles_are_unique() {
+    let mut solver = SudokuSolver::new();
+    for (size, difficulty) in [(4, Difficulty::Expert), (9, Difficulty::Easy), (9, Difficulty::Expert), (16, Difficulty::Easy)] {
+        let puzzle = solver.generate_puzzle(size, difficulty).unwrap();
+        let clues = (0..size).flat_map(|row| (0..size).map(move |col| (row, col))).filter(|&(row, col)| puzzle.cell(row, col).is_given()).count();
+        assert_eq!(puzzle.clue_count(), clues);
+        assert_eq!(puzzle.clue_count(), size * size - puzzle.empty_cells());
+        assert_eq!(solver.count_solutions(puzzle, 2), 1);
+    }
+}
+
+#[test]
+fn test_grading() {
+    let grade = sudoko::rate_puzzle("530070000600195000098000060800060003400803001700020006060000280000419005000080079", 9).unwrap();
+    assert!(grade.solved);
+    assert_eq!(grade.difficulty, Difficulty::Easy);
+    assert_eq!(grade.hardest_technique.as_deref(), Some("Hidden Singles"));
+    assert_eq!(grade.steps, 51);
+
+    let grade = sudoko::rate_puzzle("....14....3....2...7..........9...3.6.1.............8.2.....1.4....5.6.....7.8...", 9).unwrap();
+    assert_eq!(grade.rating, NakedTriples.rating());
+    assert_eq!(grade.difficulty, Difficulty::Hard);
+
+    assert_eq!(Difficulty::from_rating(NakedSingles.rating()), Difficulty::Easy);
+    assert_eq!(Difficulty::from_rating(XWing.rating()), Difficulty::Medium);
+    assert_eq!(Difficulty::from_rating(XYWing.rating()), Difficulty::Hard);
+    assert_eq!(Difficulty::from_rating(XChains.rating()), Difficulty::Expert);
+    assert!(Fish::finned(2).rating() > XWing.rating());
+
+    let solver = SudokuSolver::new();
+    assert!(matches!(solver.grade(&Sudoku::from_string("11..............", 4).unwrap()), Err(SudokuError::ContradictoryGivens { .. })));
+}
+
+#[test]
+fn test_generator_targets_difficulty() {
+    let mut solver = SudokuSolver::new();
+    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+        let puzzle = solver.generate_puzzle(9, difficulty).unwrap();
+        let grade = solver.grade(&puzzle).unwrap();
+        assert!(grade.solved && grade.difficulty <= difficulty);
+    }
+    let puzzle = solver.generate_puzzle(9, Difficulty::Easy).unwrap();
+    assert_eq!(solver.grade(&puzzle).unwrap().difficulty, Difficulty::Easy);
+}
+
+#[test]
+fn test_symmetry_orbits() {
+    assert_eq!(Symmetry::None.orbit(0, 1, 9), vec![(0, 1)]);
+    assert_eq!(Symmetry::Rotational180.orbit(0, 1, 9), vec![(0, 1), (8, 7)]);
+    assert_eq!(Symmetry::Rotational90.orbit(0, 1, 9), vec![(0, 1), (1, 8), (7, 0), (8, 7)]);
+    assert_eq!(Symmetry::HorizontalMirror.orbit(0, 1, 9), vec![(0, 1), (8, 1)]);
+    assert_eq!(Symmetry::VerticalMirror.orbit(0, 1, 9), vec![(0, 1), (0, 7)]);
+    assert_eq!(Symmetry::Diagonal.orbit(0, 1, 9), vec![(0, 1), (1, 0)]);
+    assert_eq!(Symmetry::Dihedral.orbit(0, 1, 9).len(), 8);
+    assert_eq!(Symmetry::Dihedral.orbit(0, 0, 9).len(), 4);
+    assert_eq!(Symmetry::Dihedral.orbit(4, 4, 9), vec![(4, 4)]);
+    assert_eq!(Symmetry::Diagonal.orbit(3, 3, 9), vec![(3, 3)]);
+}
+
+#[test]
+fn test_generate_with_options() {
+    let mut solver = SudokuSolver::new();
+    for symmetry in [Symmetry::Rotational90, Symmetry::HorizontalMirror, Symmetry::Diagonal, Symmetry::Dihedral] {
+        let options = GenerationOptions::new(Difficulty::Easy).with_symmetry(symmetry);
+        let puzzle = solver.generate_with_options(9, &options).unwrap();
+        for row in 0..9 {
+            for col in 0..9 {
+                let given = puzzle.cell(row, col).is_given();
+                assert!(symmetry.orbit(row, col, 9).iter().all(|&(r, c)| puzzle.cell(r, c).is_given() == given));
+            }
+        }
+    }
+
+    let options = GenerationOptions::new(Difficulty::Easy).with_symmetry(Symmetry::None).with_clues(40, 45);
+    let puzzle = solver.generate_with_options(9, &options).unwrap();
+    assert!((40..=45).contains(&puzzle.clue_count()));
+
+    let options = GenerationOptions::new(Difficulty::Medium).with_forbidden_technique("naked singles");
+    let puzzle = solver.generate_with_options(9, &options).unwrap();
+    let grade = solver.grade(&puzzle).unwrap();
+    assert!(grade.solved);
+    // Graded the way it was generated, the puzzle never needs the forbidden technique
+    let grade = solver.grade_without(&puzzle, &options.forbidden_techniques).unwrap();
+    assert!(grade.difficulty <= Difficulty::Medium);
+    assert!(!grade.techniques.contains(&"Naked Singles".to_string()));
+
+    let options = GenerationOptions::new(Difficulty::Easy).with_required_technique("Naked Singles");
+    let puzzle = solver.generate_with_options(9, &options).unwrap();
+    assert!(solver.grade(&puzzle).unwrap().techniques.contains(&"Naked Singles".to_string()));
+
+    let options = GenerationOptions::new(Difficulty::Easy).with_forbidden_technique("Guessing");
+    assert!(matches!(solver.generate_with_options(9, &options), Err(SudokuError::UnknownTechnique(_))));
+    let options = GenerationOptions::new(Difficulty::Easy).with_clues(30, 20);
+    assert!(solver.generate_with_options(9, &options).is_err());
+}
+
+#[test]
+fn test_seeded_generation_is_reproducible() {
+    let options = GenerationOptions::new(Difficulty::Medium).with_seed(7);
+    let first = SudokuSolver::new().generate_with_options(9, &options).unwrap();
+    let second = SudokuSolver::new().generate_with_options(9, &options).unwrap();
+    assert_eq!(first.to_string(), second.to_string());
+    assert_eq!(sudoko::generate_puzzle_seeded(9, Difficulty::Medium, 7).unwrap().to_string(), first.to_string());
+
+    let other = SudokuSolver::new().generate_with_options(9, &options.clone().with_seed(8)).unwrap();
+    assert_ne!(other.to_string(), first.to_string());
+}
+
+#[test]
+fn test_batch_solving() {
+    let input = "# two puzzles and a bad line\n530070000600195000098000060800060003400803001700020006060000280000419005000080079 easy\n\n12\n1.3.3.1.2.4.4.2.\n";
+    let results: Vec<_> = BatchSolver::new(input.as_bytes()).collect();
+
+    assert_eq!(results.iter().map(|result| result.line).collect::<Vec<_>>(), vec![2, 4, 5]);
+    assert_eq!(results[0].solution.as_ref().unwrap().to_line(), "534678912672195348198342567859761423426853791713924856961537284287419635345286179");
+    assert!(results[0].stats.cells_filled > 0);
+    assert!(matches!(results[1].solution, Err(SudokuError::InvalidLength { expected: 16, found: 2 })));
+    assert_eq!(results[2].solution.as_ref().unwrap().size, 4);
+
+    let sized: Vec<_> = BatchSolver::new(input.as_bytes()).with_size(9).collect();
+    assert!(sized[0].is_solved() && !sized[2].is_solved());
+}
+
+#[test]
+fn test_parallel_batch_keeps_order() {
+    let input = "530070000600195000098000060800060003400803001700020006060000280000419005000080079\n12\n1.3.3.1.2.4.4.2.\n".to_string()
+        + &"000000010400000000020000000000050407008000300001090000300400200050100000000806000\n".repeat(3);
+    let sequential: Vec<_> = BatchSolver::new(input.as_bytes()).collect();
+    let parallel: Vec<_> = BatchSolver::new(input.as_bytes()).with_jobs(4).collect();
+
+    assert_eq!(parallel.len(), 6);
+    for (a, b) in sequential.iter().zip(&parallel) {
+        assert_eq!(a.line, b.line);
+        assert_eq!(a.solution.as_ref().map(|s| s.to_line()), b.solution.as_ref().map(|s| s.to_line()));
+    }
+}
+
+#[test]
+fn test_generate_batch_is_deterministic() {
+    let options = GenerationOptions::new(Difficulty::Easy).with_seed(11);
+    let one_job = generate_batch(9, &options, 3, 1).unwrap();
+    let three_jobs = generate_batch(9, &options, 3, 3).unwrap();
+
+    assert_eq!(one_job.iter().map(|g| g.seed).collect::<Vec<_>>(), vec![11, 12, 13]);
+    for (a, b) in one_job.iter().zip(&three_jobs) {
+        assert_eq!(a.puzzle.as_ref().unwrap().to_line(), b.puzzle.as_ref().unwrap().to_line());
+    }
+    let single = SudokuSolver::new().generate_with_options(9, &options.clone().with_seed(12)).unwrap();
+    assert_eq!(single.to_line(), one_job[1].puzzle.as_ref().unwrap().to_line());
+    assert!(matches!(generate_batch(7, &options, 1, 1), Err(SudokuError::InvalidSize(7))));
+    let ten = generate_batch(10, &options, 1, 1).unwrap();
+    assert_eq!(ten[0].puzzle.as_ref().map(|puzzle| puzzle.size), Ok(10));
+}
+
+#[test]
+fn test_to_line_round_trips() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    assert_eq!(Sudoku::from_string(puzzle_str, 9).unwrap().to_line(), puzzle_str);
+    let mut puzzle = Sudoku::new(16);
+    puzzle.set(0, 0, 16).unwrap();
+    let line = puzzle.to_line();
+    assert!(line.starts_with("G0"));
+    assert_eq!(Sudoku::from_string(&line, 16).unwrap().cell(0, 0).value(), Some(16));
+}
+
+#[test]
+fn test_custom_boxes() {
+    #[rustfmt::skip]
+    let boxes = vec![
+        0, 0, 0, 1,
+        2, 0, 1, 1,
+        2, 2, 3, 1,
+        2, 3, 3, 3,
+    ];
+    let layout = Layout::with_boxes(4, boxes).unwrap();
+    assert_eq!(layout.unit_cells(Unit::new(UnitKind::Box, 0)), vec![(0, 0), (0, 1), (0, 2), (1, 1)]);
+
+    // (0, 0) and (1, 1) share a box, (1, 0) and (1, 1) only a row
+    let mut puzzle = Sudoku::with_layout(layout.clone());
+    assert!(puzzle.sees((0, 0), (1, 1)) && !puzzle.sees((0, 0), (2, 2)));
+    puzzle.set(0, 0, 1).unwrap();
+    assert!(!puzzle.has_candidate(1, 1, 1) && puzzle.has_candidate(2, 2, 1));
+    assert!(!puzzle.is_valid_placement(1, 1, 1));
+
+    let solution = SudokuSolver::new().solve(puzzle).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    for unit in solution.units() {
+        let mut values: Vec<u8> = solution.unit_cells(unit).iter().map(|&(r, c)| solution.cell(r, c).value().unwrap()).collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![1, 2, 3, 4], "{}", unit);
+    }
+
+    let mut dlx = SudokuSolver::new();
+    dlx.set_backend(SolverBackend::DancingLinks);
+    let solution = dlx.solve(Sudoku::from_string_with_layout("1000000000000000", layout).unwrap()).unwrap();
+    assert!(solution.is_valid() && solution.cell(0, 0).value() == Some(1));
+
+    assert!(matches!(Layout::with_boxes(4, vec![0; 16]), Err(SudokuError::InvalidLayout(_))));
+    assert!(matches!(Layout::with_boxes(4, vec![0, 1, 2, 3]), Err(SudokuError::InvalidLayout(_))));
+}
+
+#[test]
+fn test_extra_houses() {
+    let mut layout = Layout::standard(9).unwrap();
+    let diagonal: Vec<(usize, usize)> = (0..9).map(|i| (i, i)).collect();
+    let unit = layout.add_house(UnitKind::Extra, &diagonal).unwrap();
+    assert_eq!(unit, Unit::new(UnitKind::Extra, 0));
+    assert_eq!(layout.units().len(), 28);
+    assert!(layout.add_house(UnitKind::Box, &diagonal).is_err());
+    assert!(layout.add_house(UnitKind::Extra, &diagonal[..8]).is_err());
+
+    let mut puzzle = Sudoku::with_layout(layout.clone());
+    assert!(puzzle.sees((0, 0), (8, 8)));
+    assert_eq!(puzzle.cell_units(4, 4).len(), 4);
+    puzzle.set(0, 0, 5).unwrap();
+    assert!(!puzzle.has_candidate(8, 8, 5));
+    puzzle.set(8, 8, 5).unwrap();
+    assert!(!puzzle.is_valid());
+    let report = puzzle.validate_detailed();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].unit, unit);
+    assert_eq!(report.conflicts[0].to_string(), "5 appears 2 times in house 1: r1c1, r9c9");
+
+    for backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks] {
+        let mut solver = SudokuSolver::new();
+        solver.set_backend(backend);
+        let solution = solver.solve(Sudoku::with_layout(layout.clone())).unwrap();
+        let mut values: Vec<u8> = diagonal.iter().map(|&(r, c)| solution.cell(r, c).value().unwrap()).collect();
+        values.sort_unstable();
+        assert_eq!(values, (1..=9).collect::<Vec<u8>>());
+    }
 }
 
 #[test]
@@ -121,3 +575,601 @@ fn test_solver_stats() {
     assert!(stats.iterations > 0);
     assert!(stats.cells_filled > 0);
 }
+
+#[test]
+fn test_candidate_grid_tracks_placements() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let mut puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    assert_eq!(puzzle.candidate_mask(0, 0), 0);
+    assert!(puzzle.has_candidate(0, 2, 4));
+    assert_eq!(puzzle.candidate_values(0, 2), vec![1, 2, 4]);
+
+    puzzle.set(0, 2, 4).unwrap();
+    assert_eq!(puzzle.candidate_mask(0, 2), 0);
+    assert!(!puzzle.has_candidate(0, 3, 4));
+    assert!(!puzzle.has_candidate(2, 0, 4));
+    assert!(!puzzle.has_candidate(8, 2, 4));
+}
+
+#[test]
+fn test_candidate_elimination() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let mut puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    assert!(puzzle.eliminate_candidate(0, 2, 1));
+    assert!(!puzzle.eliminate_candidate(0, 2, 1));
+    assert!(!puzzle.get_candidates(0, 2).contains(&1));
+    assert_eq!(puzzle.candidate_count(0, 2), 2);
+
+    // Clearing a placed value invalidates earlier eliminations
+    puzzle.set(0, 3, 6).unwrap();
+    puzzle.set(0, 3, 0).unwrap();
+    assert!(puzzle.has_candidate(0, 2, 1));
+
+    puzzle.eliminate_candidate(0, 2, 2);
+    puzzle.reset_candidates();
+    assert_eq!(puzzle.candidate_values(0, 2), vec![1, 2, 4]);
+}
+
+#[test]
+fn test_naked_pairs_eliminations() {
+    let mut puzzle = Sudoku::new(9);
+    for value in 3..=9 {
+        puzzle.eliminate_candidate(0, 0, value);
+        puzzle.eliminate_candidate(0, 4, value);
+    }
+
+    let eliminations = NakedPairs.find_eliminations(&puzzle);
+    assert!(eliminations.contains(&Elimination::new(0, 8, 1)));
+    assert!(eliminations.contains(&Elimination::new(0, 1, 2)));
+    assert!(!eliminations.iter().any(|e| e.row != 0));
+
+    assert!(NakedPairs.apply(&mut puzzle));
+    assert_eq!(puzzle.candidate_values(0, 8), (3..=9).collect::<Vec<u8>>());
+    assert!(NakedPairs.find_eliminations(&puzzle).is_empty());
+}
+
+#[test]
+fn test_hidden_pairs_eliminations() {
+    let mut puzzle = Sudoku::new(9);
+    for col in 2..9 {
+        puzzle.eliminate_candidate(0, col, 1);
+        puzzle.eliminate_candidate(0, col, 2);
+    }
+
+    let eliminations = HiddenPairs.find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 14);
+    assert!(eliminations.iter().all(|e| e.row == 0 && e.col < 2 && e.value > 2));
+}
+
+#[test]
+fn test_subsets_solve_without_backtracking() {
+    let puzzle_str = "000000000904607000076804100309701080008000300050308702007502610000403208000000000";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    let mut solver = SudokuSolver::new();
+    solver.set_use_backtracking(false);
+    let (solution, stats) = solver.solve_with_stats(puzzle).unwrap();
+
+    assert!(solution.is_complete() && solution.is_valid());
+    assert_eq!(stats.backtrack_steps, 0);
+}
+
+#[test]
+fn test_pointing_pairs_and_box_line_reduction() {
+    // Confine 5 in the top-left box to row 0
+    let mut puzzle = Sudoku::new(9);
+    for row in 1..3 {
+        for col in 0..3 {
+            puzzle.eliminate_candidate(row, col, 5);
+        }
+    }
+
+    let eliminations = PointingPairs.find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 6);
+    assert!(eliminations.iter().all(|e| e.row == 0 && e.col >= 3 && e.value == 5));
+
+    // Confine 7 in row 4 to the middle box
+    let mut puzzle = Sudoku::new(9);
+    for col in (0..3).chain(6..9) {
+        puzzle.eliminate_candidate(4, col, 7);
+    }
+
+    let eliminations = BoxLineReduction.find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 6);
+    assert!(eliminations.contains(&Elimination::new(3, 4, 7)));
+    assert!(!eliminations.iter().any(|e| e.row == 4));
+}
+
+#[test]
+fn test_solver_counts_eliminations() {
+    let puzzle_str = "930050000200630095856002000003180570005020980080005000000800159508210004000560008";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    let mut solver = SudokuSolver::new();
+    solver.set_use_backtracking(false);
+    let (solution, stats) = solver.solve_with_stats(puzzle).unwrap();
+
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.candidates_eliminated > 0);
+    assert!(stats.strategies_used.contains_key("Pointing Pairs"));
+}
+
+#[test]
+fn test_x_wing_and_finned_x_wing() {
+    // 1 in rows 0 and 4 is confined to columns 1 and 7
+    let mut puzzle = Sudoku::new(9);
+    for row in [0, 4] {
+        for col in (0..9).filter(|&col| col != 1 && col != 7) {
+            puzzle.eliminate_candidate(row, col, 1);
+        }
+    }
+
+    let eliminations = XWing.find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 14);
+    assert!(eliminations.iter().all(|e| (e.col == 1 || e.col == 7) && e.row != 0 && e.row != 4));
+
+    // A fin at r1c9 limits the eliminations to the fin's box
+    let mut puzzle = Sudoku::new(9);
+    for row in [0, 4] {
+        for col in (0..9).filter(|&col| col != 1 && col != 7 && (row, col) != (0, 8)) {
+            puzzle.eliminate_candidate(row, col, 1);
+        }
+    }
+
+    assert!(XWing.find_eliminations(&puzzle).is_empty());
+    let eliminations = Fish::finned(2).find_eliminations(&puzzle);
+    assert_eq!(
+        eliminations,
+        vec![Elimination::new(1, 7, 1), Elimination::new(2, 7, 1)]
+    );
+}
+
+#[test]
+fn test_large_fish_up_to_half_the_grid() {
+    // On 16x16, 1 in rows 0-7 is confined to columns 0-7: an eight-line fish
+    let mut puzzle = Sudoku::new(16);
+    for row in 0..8 {
+        for col in 8..16 {
+            puzzle.eliminate_candidate(row, col, 1);
+        }
+    }
+
+    let eliminations = Fish::new(8).find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 64);
+    assert!(eliminations.iter().all(|e| e.row >= 8 && e.col < 8));
+
+    // The default strategy list carries fish of that order
+    assert!(sudoko::get_all_strategies()
+        .iter()
+        .any(|strategy| strategy.find_eliminations(&puzzle) == eliminations));
+}
+
+#[test]
+fn test_fish_solve_without_backtracking() {
+    let puzzle_str = "100000569492056108056109240009640801064010000218035604040500016905061402621000005";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    let mut solver = SudokuSolver::new();
+    solver.set_use_backtracking(false);
+    let (solution, stats) = solver.solve_with_stats(puzzle).unwrap();
+
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.strategies_used.contains_key("X-Wing"));
+}
+
+#[test]
+fn test_xy_and_xyz_wings() {
+    let puzzle = grid_with_candidates(&[((0, 0), &[1, 2]), ((0, 4), &[1, 3]), ((4, 0), &[2, 3])]);
+    assert_eq!(
+        XYWing.find_eliminations(&puzzle),
+        vec![Elimination::new(4, 4, 3)]
+    );
+
+    let puzzle = grid_with_candidates(&[((0, 0), &[1, 2, 3]), ((0, 1), &[1, 3]), ((1, 0), &[2, 3])]);
+    let eliminations = XYZWing.find_eliminations(&puzzle);
+    assert_eq!(eliminations.len(), 6);
+    assert!(eliminations.iter().all(|e| e.row < 3 && e.col < 3 && e.value == 3));
+}
+
+#[test]
+fn test_w_wing() {
+    let mut puzzle = grid_with_candidates(&[((0, 0), &[1, 2]), ((4, 4), &[1, 2])]);
+    for col in (0..9).filter(|&col| col != 0 && col != 4) {
+        puzzle.eliminate_candidate(8, col, 1);
+    }
+
+    assert_eq!(
+        WWing.find_eliminations(&puzzle),
+        vec![Elimination::new(0, 4, 2), Elimination::new(4, 0, 2)]
+    );
+}
+
+#[test]
+fn test_single_digit_chains() {
+    // Strong links on 1: r1c1=r6c1, r6c1=r6c4 and r6c4=r3c4
+    let mut puzzle = Sudoku::new(9);
+    for row in (0..9).filter(|&row| row != 0 && row != 5) {
+        puzzle.eliminate_candidate(row, 0, 1);
+    }
+    for row in (0..9).filter(|&row| row != 2 && row != 5) {
+        puzzle.eliminate_candidate(row, 3, 1);
+    }
+
+    let expected = vec![
+        Elimination::new(0, 4, 1),
+        Elimination::new(0, 5, 1),
+        Elimination::new(2, 1, 1),
+        Elimination::new(2, 2, 1),
+    ];
+    let mut eliminations = XChains.find_eliminations(&puzzle);
+    eliminations.sort_by_key(|e| (e.row, e.col));
+    assert_eq!(eliminations, expected);
+
+    for col in (0..9).filter(|&col| col != 0 && col != 3) {
+        puzzle.eliminate_candidate(5, col, 1);
+    }
+    let mut eliminations = SimpleColoring.find_eliminations(&puzzle);
+    eliminations.sort_by_key(|e| (e.row, e.col));
+    assert_eq!(eliminations, expected);
+}
+
+#[test]
+fn test_alternating_inference_chain() {
+    let puzzle = grid_with_candidates(&[((0, 0), &[1, 2]), ((0, 4), &[1, 3]), ((4, 0), &[2, 3])]);
+    let deduction = AlternatingInferenceChains::new()
+        .find_deduction(&puzzle)
+        .unwrap();
+
+    assert_eq!(deduction.eliminations, vec![Elimination::new(4, 4, 3)]);
+    assert_eq!(
+        deduction.chain.to_string(),
+        "(3)r1c5=(1)r1c5-(1)r1c1=(2)r1c1-(2)r5c1=(3)r5c1"
+    );
+    assert!(deduction
+        .chain
+        .explain()
+        .starts_with("If r1c5 is not 3, then r1c5 is 1, then r1c1 is not 1"));
+}
+
+#[test]
+fn test_chains_solve_hard_puzzle() {
+    let puzzle = Sudoku::from_string(
+        "....3..6.29...6...7..29.51...8..3.5...............4387..1..9.....376.29.........5",
+        9,
+    )
+    .unwrap();
+    let mut solver = SudokuSolver::new();
+    solver.set_use_backtracking(false);
+    let (solution, stats) = solver.solve_with_stats(puzzle.clone()).unwrap();
+
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.strategies_used.contains_key("Alternating Inference Chain"));
+
+    // Every other strategy stalls on it
+    let strategies = sudoko::get_all_strategies()
+        .into_iter()
+        .filter(|strategy| strategy.name() != "Alternating Inference Chain")
+        .collect();
+    let mut solver = SudokuSolver::new_with_strategies(strategies);
+    solver.set_use_backtracking(false);
+    assert!(matches!(solver.solve(puzzle), Err(SudokuError::Unsolvable)));
+}
+
+#[test]
+fn test_unique_rectangle_type_1() {
+    let puzzle = grid_with_candidates(&[
+        ((0, 0), &[1, 2]),
+        ((0, 3), &[1, 2]),
+        ((1, 0), &[1, 2]),
+        ((1, 3), &[1, 2, 5]),
+    ]);
+
+    assert!(UniqueRectangles.requires_uniqueness());
+    assert_eq!(
+        UniqueRectangles.find_eliminations(&puzzle),
+        vec![Elimination::new(1, 3, 1), Elimination::new(1, 3, 2)]
+    );
+}
+
+#[test]
+fn test_uniqueness_strategies_are_opt_in() {
+    let puzzle_str = "720408030080000047401076802810739000000851000000264080209680413340000008168943275";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+
+    let mut solver = SudokuSolver::new();
+    let (_, stats) = solver.solve_with_stats(puzzle.clone()).unwrap();
+    assert!(!stats.strategies_used.contains_key("Unique Rectangle"));
+
+    solver.set_assume_unique_solution(true);
+    let (solution, stats) = solver.solve_with_stats(puzzle).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.strategies_used.contains_key("Unique Rectangle"));
+}
+
+#[test]
+fn test_strategy_steps() {
+    let puzzle = grid_with_candidates(&[((0, 0), &[1, 2]), ((0, 4), &[1, 2]), ((0, 6), &[1, 2, 3])]);
+
+    let step = NakedPairs.find_step(&puzzle).unwrap();
+    assert_eq!(step.technique, "Naked Pairs");
+    assert_eq!(step.cells, vec![(0, 0), (0, 4)]);
+    assert_eq!(step.units, vec![Unit::new(UnitKind::Row, 0)]);
+    assert!(step.placements.is_empty());
+    assert!(step.eliminations.contains(&Elimination::new(0, 6, 1)));
+    assert!(step.to_string().starts_with("Naked Pairs in row 1: r1c2<>1, r1c2<>2"));
+
+    let mut grid = puzzle.clone();
+    assert!(step.apply(&mut grid));
+    assert_eq!(grid.candidate_values(0, 6), vec![3]);
+}
+
+#[test]
+fn test_solver_records_steps() {
+    let puzzle_str = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let puzzle = Sudoku::from_string(puzzle_str, 9).unwrap();
+    let mut solver = SudokuSolver::new();
+
+    let step = solver.next_step(&puzzle).unwrap();
+    assert_eq!(step.technique, "Naked Singles");
+    assert_eq!(step.placements.len(), 1);
+
+    let (solution, stats) = solver.solve_with_stats(puzzle.clone()).unwrap();
+    let placed: usize = stats.steps.iter().map(|step| step.placements.len()).sum();
+    assert_eq!(placed, 81 - 30);
+
+    // Replaying the recorded steps solves the puzzle again
+    let mut replay = puzzle;
+    for step in &stats.steps {
+        step.apply(&mut replay);
+    }
+    assert_eq!(replay.to_string(), solution.to_string());
+
+    let mut stepped = Sudoku::from_string(puzzle_str, 9).unwrap();
+    let steps: Vec<Step> = std::iter::from_fn(|| solver.solve_step(&mut stepped)).collect();
+    assert!(stepped.is_complete() && stepped.is_valid());
+    assert!(steps.iter().all(|step| !step.placements.is_empty()));
+}
+
+#[test]
+fn test_jigsaw() {
+    let regions = "111222333114222333114255333114255566444256666444555776888999776889999776888899777";
+    let puzzle_str = "200000509100000000000240060430100200080000000600009800008070000000600000000000003";
+    let mut puzzle = Sudoku::from_jigsaw(puzzle_str, regions, 9).unwrap();
+    assert!(puzzle.layout().is_jigsaw() && !Layout::standard(9).unwrap().is_jigsaw());
+    assert_eq!(puzzle.layout().region_map().replace('\n', ""), regions);
+    // r2c3 is in the fourth region, with r5c1, not with r1c2 as in the first box
+    assert_eq!(puzzle.box_index(1, 2), 3);
+    assert!(puzzle.sees((1, 2), (4, 0)) && !puzzle.sees((1, 2), (0, 1)));
+
+    let hint = SudokuSolver::new().get_hint(&mut puzzle).unwrap();
+    let (solution, _) = SudokuSolver::new().solve_with_stats(puzzle.clone()).unwrap();
+    assert_eq!(solution.to_line(), "276834519153796482891245367435187296984562731627319854368471925549623178712958643");
+    assert_eq!(solution.cell(hint.0, hint.1).value(), Some(hint.2));
+    assert!(matches!(SudokuSolver::new().analyze(&puzzle).uniqueness, Uniqueness::Unique(_)));
+
+    // Region borders are drawn between cells, with corners where they meet
+    let shown = puzzle.to_string();
+    assert!(shown.starts_with("2 . .|. . .|5 . 9\n   +-+     |\n1 .|.|"), "{}", shown);
+    assert!(Sudoku::from_string(puzzle_str, 9).unwrap().to_string().starts_with("2 . . |. . . |5 . 9 \n"));
+
+    // The regions must partition the grid into connected pieces of nine cells
+    let error = |map: &str| match Layout::from_region_map(map, 9) {
+        Err(SudokuError::InvalidLayout(reason)) => reason,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(error(&regions[..80]), "region map has 80 cells, expected 81");
+    assert_eq!(error(&format!("{}8", &regions[..80])), "region '7' has 8 cells, expected 9");
+    assert_eq!(error(&format!("{}A", &regions[..80])), "region map has 10 regions, expected 9");
+    assert_eq!(error(&format!("7{}1", &regions[1..80])), "region '7' is not connected");
+    let spaced: String = regions.as_bytes().chunks(9).map(|row| format!("{}\n", std::str::from_utf8(row).unwrap())).collect();
+    assert_eq!(Layout::from_region_map(&spaced, 9).unwrap(), *puzzle.layout());
+}
+
+#[test]
+fn test_variants() {
+    assert_eq!(Variant::parse_list("x, anti-knight").unwrap(), vec![Variant::Diagonal, Variant::AntiKnight]);
+    assert!(matches!("bishop".parse::<Variant>(), Err(SudokuError::UnknownVariant(name)) if name == "bishop"));
+
+    // Diagonal and Windoku add houses; adding a rule twice changes nothing
+    let standard = Layout::standard(9).unwrap().units().len();
+    let x = Layout::with_variants(9, &[Variant::Diagonal, Variant::Diagonal]).unwrap();
+    assert_eq!(x.units().len(), standard + 2);
+    assert_eq!(x.unit_cells(Unit::new(UnitKind::Diagonal, 1))[0], (0, 8));
+    let hyper = Layout::with_variants(9, &[Variant::Windoku]).unwrap();
+    assert_eq!(hyper.units().len(), standard + 4);
+    assert_eq!(hyper.unit_cells(Unit::new(UnitKind::Window, 3)), vec![(5, 5), (5, 6), (5, 7), (6, 5), (6, 6), (6, 7), (7, 5), (7, 6), (7, 7)]);
+    assert!(Sudoku::with_layout(hyper).sees((1, 1), (3, 3)));
+
+    // Anti-king cells touching diagonally must differ, though they share no house
+    let mut puzzle = Sudoku::with_layout(Layout::with_variants(9, &[Variant::AntiKing]).unwrap());
+    puzzle.set(0, 2, 1).unwrap();
+    assert!(!puzzle.is_valid_placement(1, 3, 1) && !puzzle.has_candidate(1, 3, 1) && puzzle.is_valid_placement(2, 3, 1));
+    puzzle.set(1, 3, 1).unwrap();
+    let report = puzzle.validate_detailed();
+    assert!(report.conflicts.is_empty() && !report.is_valid() && !puzzle.is_valid());
+    assert_eq!(report.clashes[0].to_string(), "1 appears in neighbouring cells r1c3 and r2c4");
+
+    // Generated puzzles keep to the rules and have one solution under them
+    let options = GenerationOptions::new(Difficulty::Easy).with_seed(3).with_variant(Variant::AntiKnight);
+    let generated = SudokuSolver::new().generate_with_options(9, &options).unwrap();
+    assert_eq!(generated.layout().variants(), &[Variant::AntiKnight]);
+    let mut dlx = SudokuSolver::new();
+    dlx.set_backend(SolverBackend::DancingLinks);
+    let solution = match dlx.analyze(&generated).uniqueness {
+        Uniqueness::Unique(solution) => solution,
+        other => panic!("{:?}", other),
+    };
+    for (row, col) in (0..9usize).flat_map(|row| (0..9usize).map(move |col| (row, col))) {
+        for (r, c) in [(row + 1, col + 2), (row + 2, col + 1), (row + 1, col.wrapping_sub(2)), (row + 2, col.wrapping_sub(1))] {
+            if r < 9 && c < 9 {
+                assert_ne!(solution.cell(row, col).value(), solution.cell(r, c).value());
+            }
+        }
+    }
+
+    let results: Vec<_> = BatchSolver::new(generated.to_line().as_bytes()).with_variant(Variant::AntiKnight).collect();
+    assert_eq!(results[0].solution.as_ref().unwrap().to_line(), solution.to_line());
+}
+
+#[test]
+fn test_killer() {
+    let cage: Cage = "15: r1c1, r1c2 r2c1".parse().unwrap();
+    assert_eq!(cage, Cage::new(15, vec![(0, 0), (0, 1), (1, 0)]));
+    assert_eq!(cage.to_string(), "15: r1c1 r1c2 r2c1");
+    assert_eq!(Cage::parse_list("# comment\n3: r1c1 r1c2; 4: r2c2\n").unwrap().len(), 2);
+    assert!(matches!("r1c1 r1c2".parse::<Cage>(), Err(SudokuError::InvalidCage(_))));
+    assert!(matches!("9: r1".parse::<Cage>(), Err(SudokuError::InvalidCage(message)) if message == "'r1' is not a cell like r1c2"));
+
+    // Cages may not overlap or ask for a sum their cells cannot make
+    let mut layout = Layout::standard(9).unwrap();
+    layout.add_cage(cage).unwrap();
+    assert!(matches!(layout.add_cage(Cage::new(3, vec![(0, 1), (0, 2)])), Err(SudokuError::InvalidCage(message)) if message == "r1c2 is in more than one cage"));
+    assert!(matches!(layout.add_cage(Cage::new(2, vec![(5, 5), (5, 6)])), Err(SudokuError::InvalidCage(message)) if message == "2 different values cannot add up to 2"));
+    assert!(layout.is_killer() && layout.cage_index(1, 0) == Some(0) && layout.cage_index(1, 1).is_none());
+
+    // Cage mates must differ, and a full cage must add up to its sum
+    let mut puzzle = Sudoku::with_layout(layout);
+    assert!(puzzle.sees((0, 1), (1, 0)));
+    puzzle.set(0, 0, 9).unwrap();
+    puzzle.set(0, 1, 5).unwrap();
+    assert!(puzzle.is_valid());
+    puzzle.set(1, 0, 2).unwrap();
+    let report = puzzle.validate_detailed();
+    assert!(report.conflicts.is_empty() && !report.is_valid() && !puzzle.is_valid());
+    assert_eq!(report.cage_errors[0].to_string(), "cage 1 adds up to 16, not 15");
+    assert_eq!(report.conflicting_cells(), vec![(0, 0), (0, 1), (1, 0)]);
+
+    // A puzzle with few givens is solved by its cages
+    let cages = "23: r1c1 r1c2 r2c2 r2c3 r3c2; 27: r1c3 r1c4 r1c5 r1c6 r2c5; 15: r1c7 r1c8; 19: r1c9 r2c8 r2c9; 25: r2c1 r3c1 r4c1 r5c1 r6c1; 26: r2c4 r3c3 r3c4 r3c5 r3c6; 11: r2c6 r2c7 r3c7 r3c8; 30: r3c9 r4c8 r4c9 r5c7 r5c8; 19: r4c2 r5c2 r6c2 r7c2; 24: r4c3 r5c3 r6c3 r7c3; 19: r4c4 r4c5 r4c6 r4c7; 9: r5c4 r5c5; 30: r5c6 r6c5 r6c6 r7c6 r8c6; 11: r5c9 r6c9 r7c9; 17: r6c4 r7c4 r7c5; 3: r6c7 r6c8; 4: r7c1; 24: r7c7 r8c7 r9c6 r9c7; 12: r7c8 r8c8 r9c8; 23: r8c1 r8c2 r8c3 r9c1 r9c2; 22: r8c4 r8c5 r9c3 r9c4 r9c5; 12: r8c9 r9c9";
+    let puzzle = Sudoku::from_killer("010060700003000000000000000000000000000000000000000000000000000000000500007030020", cages, 9).unwrap();
+    assert_eq!(puzzle.layout().cages().len(), 22);
+    assert_eq!(Cage::parse_list(&puzzle.layout().cage_definitions()).unwrap(), puzzle.layout().cages());
+    let (solution, stats) = SudokuSolver::new().solve_with_stats(puzzle.clone()).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert_eq!(stats.backtrack_steps, 0);
+    assert!(stats.cage_techniques.contains(&"Cage Combinations".to_string()));
+    let mut dlx = SudokuSolver::new();
+    dlx.set_backend(SolverBackend::DancingLinks);
+    assert!(matches!(dlx.analyze(&puzzle).uniqueness, Uniqueness::Unique(found) if found.to_line() == solution.to_line()));
+
+    // Generated Killer puzzles cover the grid with cages and have one solution
+    let options = GenerationOptions::new(Difficulty::Easy).with_seed(7).with_killer_cages(5);
+    let generated = SudokuSolver::new().generate_with_options(9, &options).unwrap();
+    let cages = generated.layout().cages();
+    assert!(cages.iter().all(|cage| cage.cells.len() <= 5));
+    assert_eq!(cages.iter().map(|cage| cage.cells.len()).sum::<usize>(), 81);
+    assert_eq!(cages.iter().map(|cage| cage.sum).sum::<u32>(), 405);
+    assert!(matches!(dlx.analyze(&generated).uniqueness, Uniqueness::Unique(_)));
+}
+
+#[test]
+fn test_rectangular_boxes() {
+    assert_eq!(Layout::box_shape_for(6), Some((2, 3)));
+    assert_eq!(Layout::box_shape_for(8), Some((2, 4)));
+    assert_eq!(Layout::box_shape_for(10), Some((2, 5)));
+    assert_eq!(Layout::box_shape_for(12), Some((3, 4)));
+    assert_eq!(Layout::box_shape_for(16), Some((4, 4)));
+    assert_eq!(Layout::box_shape_for(7), None);
+    assert!(matches!(Layout::rectangular(1, 6), Err(SudokuError::InvalidLayout(_))));
+
+    // 6x6 grids have boxes 2 rows tall and 3 columns wide unless told otherwise
+    let puzzle = Sudoku::from_string("000004400160010200004030043002600000", 6).unwrap();
+    assert_eq!((puzzle.box_rows, puzzle.box_cols), (2, 3));
+    assert_eq!(puzzle.layout().box_shape(), Some((2, 3)));
+    assert_eq!(puzzle.box_index(1, 3), 1);
+    assert_eq!(puzzle.box_index(2, 0), 2);
+    assert!(!puzzle.layout().is_jigsaw());
+    assert_eq!(puzzle.to_string().lines().nth(2), Some("-------------"));
+    let solution = SudokuSolver::new().solve(puzzle.clone()).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    let mut dlx = SudokuSolver::new();
+    dlx.set_backend(SolverBackend::DancingLinks);
+    assert!(matches!(dlx.analyze(&puzzle).uniqueness, Uniqueness::Unique(found) if found.to_line() == solution.to_line()));
+
+    // Boxes 3 rows tall and 2 columns wide make different houses on the same grid
+    let tall = Sudoku::from_string_with_layout("100000640300020600002060006041000002", Layout::rectangular(3, 2).unwrap()).unwrap();
+    assert_eq!((tall.box_rows, tall.box_cols), (3, 2));
+    assert_eq!(tall.box_index(2, 1), 0);
+    assert_eq!(tall.box_index(0, 2), 1);
+    let mut invalid = Sudoku::with_layout(Layout::rectangular(3, 2).unwrap());
+    invalid.set(0, 0, 1).unwrap();
+    invalid.set(2, 1, 1).unwrap();
+    assert!(!invalid.is_valid());
+
+    // Generation follows the box shape, and batches find 12x12 grids from their length
+    let options = GenerationOptions::new(Difficulty::Easy).with_seed(4).with_box_shape(3, 2);
+    let generated = SudokuSolver::new().generate_with_options(6, &options).unwrap();
+    assert_eq!(generated.layout().box_shape(), Some((3, 2)));
+    assert!(matches!(dlx.analyze(&generated).uniqueness, Uniqueness::Unique(_)));
+    assert!(matches!(SudokuSolver::new().generate_with_options(9, &options), Err(SudokuError::InvalidLayout(_))));
+    let twelve = sudoko::generate_puzzle_seeded(12, Difficulty::Easy, 4).unwrap();
+    assert_eq!((twelve.box_rows, twelve.box_cols), (3, 4));
+    let results: Vec<_> = BatchSolver::new(twelve.to_line().as_bytes()).collect();
+    assert!(results[0].is_solved());
+    let results: Vec<_> = BatchSolver::new(tall.to_line().as_bytes()).with_box_shape(3, 2).collect();
+    assert_eq!(results[0].solution.as_ref().unwrap().layout().box_shape(), Some((3, 2)));
+    assert!(matches!(Layout::with_variants(6, &[Variant::Windoku]), Err(SudokuError::InvalidLayout(_))));
+}
+
+/// A strategy from outside the library, giving no rating
+struct NothingFound;
+
+impl SolvingStrategy for NothingFound {
+    fn name(&self) -> &'static str {
+        "Nothing Found"
+    }
+
+    fn apply(&self, _sudoku: &mut Sudoku) -> bool {
+        false
+    }
+}
+
+/// A strategy written against the trait's original `name` and `apply` only
+struct LoneCandidates;
+
+impl SolvingStrategy for LoneCandidates {
+    fn name(&self) -> &'static str {
+        "Lone Candidates"
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> bool {
+        let mut progress = false;
+        for row in 0..sudoku.size {
+            for col in 0..sudoku.size {
+                if sudoku.cell(row, col).is_empty() && sudoku.candidate_count(row, col) == 1 {
+                    let value = (1..=sudoku.size as u8).find(|&value| sudoku.has_candidate(row, col, value)).unwrap();
+                    progress |= sudoku.set(row, col, value).is_ok();
+                }
+            }
+        }
+        progress
+    }
+}
+
+#[test]
+fn test_apply_only_strategy_reports_steps() {
+    let puzzle = Sudoku::from_string("530070000600195000098000060800060003400803001700020006060000280000419005000080079", 9).unwrap();
+
+    let steps = LoneCandidates.find_steps(&puzzle);
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].technique, "Lone Candidates");
+    assert!(!steps[0].placements.is_empty());
+    assert!(steps[0].placements.iter().all(|p| puzzle.has_candidate(p.row, p.col, p.value)));
+
+    let mut solver = SudokuSolver::new_with_strategies(vec![Box::new(LoneCandidates), Box::new(sudoko::HiddenSingles)]);
+    solver.set_use_backtracking(false);
+    let (solution, stats) = solver.solve_with_stats(puzzle).unwrap();
+    assert!(solution.is_complete() && solution.is_valid());
+    assert!(stats.strategies_used.contains_key("Lone Candidates"));
+}
+
+#[test]
+fn test_unrated_strategy_grades_hardest() {
+    assert_eq!(NothingFound.rating(), 11.0);
+    assert_eq!(Difficulty::from_rating(NothingFound.rating()), Difficulty::Expert);
+    let mut solver = SudokuSolver::new_with_strategies(vec![Box::new(NakedSingles), Box::new(NothingFound)]);
+    let puzzle = Sudoku::from_string("530070000600195000098000060800060003400803001700020006060000280000419005000080079", 9).unwrap();
+    assert!(solver.solve(puzzle).unwrap().is_complete());
+}